@@ -0,0 +1,92 @@
+//! DOCX conversion of image-heavy documents (`cargo bench --bench docx_images`).
+//! The documents are generated in memory: one paragraph per figure, each figure
+//! its own picture. With pandoc on PATH, `docx2md` hands the file to pandoc and
+//! that is what gets measured; run without it to time the built-in reader.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use markitup::config::Settings;
+use markitup::{convert_bytes_as, ConvertOptions};
+use std::io::{Cursor, Write};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+const DOCX_MIME: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+fn tiny_png(shade: u8) -> Vec<u8> {
+    let image = image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(4, 4, image::Rgb([shade, 0, 0])));
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), image::ImageOutputFormat::Png).unwrap();
+    png
+}
+
+fn figure_paragraph(index: usize) -> String {
+    format!(
+        r#"<w:p><w:r><w:t>Figure {index}</w:t></w:r></w:p><w:p><w:r><w:drawing><wp:inline><wp:extent cx="38100" cy="38100"/><wp:docPr id="{index}" name="Picture {index}" descr="Figure {index}"/><a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:pic><pic:nvPicPr><pic:cNvPr id="{index}" name="image{index}.png"/><pic:cNvPicPr/></pic:nvPicPr><pic:blipFill><a:blip r:embed="rId{index}"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill><pic:spPr/></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#
+    )
+}
+
+// A DOCX with `figures` pictures, each in word/media/ and linked from document.xml
+fn image_heavy_docx(figures: usize) -> Vec<u8> {
+    let mut body = String::new();
+    let mut rels = String::new();
+    for index in 1..=figures {
+        body.push_str(&figure_paragraph(index));
+        rels.push_str(&format!(
+            r#"<Relationship Id="rId{index}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image{index}.png"/>"#
+        ));
+    }
+
+    let parts = [
+        (
+            "[Content_Types].xml",
+            r#"<?xml version="1.0" encoding="UTF-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Default Extension="png" ContentType="image/png"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#.to_string(),
+        ),
+        (
+            "_rels/.rels",
+            r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#.to_string(),
+        ),
+        (
+            "word/document.xml",
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture"><w:body>{body}</w:body></w:document>"#
+            ),
+        ),
+        (
+            "word/_rels/document.xml.rels",
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{rels}</Relationships>"#
+            ),
+        ),
+    ];
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in parts {
+        zip.start_file(name, FileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    for index in 1..=figures {
+        zip.start_file(format!("word/media/image{}.png", index), FileOptions::default()).unwrap();
+        zip.write_all(&tiny_png(index as u8)).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+fn docx_images(c: &mut Criterion) {
+    // Inline data URIs: no AI calls, nothing written to disk
+    let settings = Settings::from_toml_str("is_ai_enpower = false\nimage_mode = \"base64\"").unwrap();
+    let options = ConvertOptions::new(settings);
+
+    let mut group = c.benchmark_group("docx_images");
+    group.sample_size(10);
+    for figures in [50, 200, 800] {
+        let docx = image_heavy_docx(figures);
+        group.throughput(Throughput::Elements(figures as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(figures), &docx, |b, docx| {
+            b.iter(|| convert_bytes_as(docx, DOCX_MIME, &options).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, docx_images);
+criterion_main!(benches);
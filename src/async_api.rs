@@ -0,0 +1,22 @@
+//! Async entry points (feature `async`) for tokio-based services.
+//!
+//! Conversion itself stays synchronous: parsing, image encoding, pandoc/LibreOffice
+//! subprocesses and the Vosk recognizer all block. `convert_async` therefore moves the
+//! whole conversion onto tokio's blocking thread pool via `spawn_blocking`, so the
+//! async worker threads keep serving other tasks. That pool grows on demand up to the
+//! runtime's `max_blocking_threads` (512 by default), so bound concurrency yourself
+//! (e.g. with a semaphore) if many large files may arrive at once. A started
+//! conversion runs to completion even if the returned future is dropped.
+//!
+//! Must be called from within a tokio runtime.
+
+use crate::{ConvertError, ConverterFile};
+
+/// `convert` on tokio's blocking thread pool
+pub async fn convert_async(file: ConverterFile) -> Result<String, ConvertError> {
+    tokio::task::spawn_blocking(move || crate::convert(file))
+        .await
+        .map_err(|e| ConvertError::Conversion(format!("Conversion task failed: {}", e)))?
+}
+
+pub use crate::generator::image2md::ai_generate_name_async;
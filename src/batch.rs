@@ -0,0 +1,109 @@
+//! Directory (batch) conversion helpers
+//! Collects input files from a directory tree and keeps a small sidecar
+//! manifest of content hashes so unchanged files can be skipped on re-runs.
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE_NAME: &str = ".markitup-manifest.json";
+
+/// Recursively collect convertible files under `dir`, sorted by path.
+/// Hidden files and existing Markdown outputs are skipped.
+pub fn collect_inputs(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    collect_into(dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_into(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Failed to read directory entry: {}", e))?
+            .path();
+        let is_hidden = path
+            .file_name()
+            .map(|name| name.to_string_lossy().starts_with('.'))
+            .unwrap_or(false);
+        if is_hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_into(&path, files)?;
+        } else if path.extension().map(|ext| ext != "md").unwrap_or(true) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Path of the Markdown output for `input`, mirroring its place under `input_root`
+pub fn output_path_for(input: &Path, input_root: &Path, output_root: &Path) -> PathBuf {
+    let relative = input.strip_prefix(input_root).unwrap_or(input);
+    output_root.join(relative).with_extension("md")
+}
+
+/// Sub-directory name for one input's images when several inputs share an
+/// image path, e.g. `reports/q1.docx` -> `reports_q1_docx`
+pub fn image_namespace(input: &Path, input_root: &Path) -> String {
+    let relative = input.strip_prefix(input_root).unwrap_or(input);
+    relative
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect()
+}
+
+/// Hex-encoded SHA-256 of the input bytes
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// True when `output` exists and was modified no earlier than `input`
+pub fn output_is_fresh(input: &Path, output: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(input), modified(output)) {
+        (Some(input_time), Some(output_time)) => output_time >= input_time,
+        _ => false,
+    }
+}
+
+/// Content hashes of previously converted inputs, keyed by path relative to the input root
+#[derive(Debug, Default)]
+pub struct Manifest {
+    path: PathBuf,
+    entries: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Load the manifest stored in `output_root`, or start an empty one
+    pub fn load(output_root: &Path) -> Self {
+        let path = output_root.join(MANIFEST_FILE_NAME);
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path, entries }
+    }
+
+    pub fn is_unchanged(&self, key: &str, hash: &str) -> bool {
+        self.entries.get(key).map(|h| h == hash).unwrap_or(false)
+    }
+
+    pub fn record(&mut self, key: String, hash: String) {
+        self.entries.insert(key, hash);
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(&self.entries)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+        fs::write(&self.path, content)
+            .map_err(|e| format!("Failed to write manifest {}: {}", self.path.display(), e))
+    }
+}
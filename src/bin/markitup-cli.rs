@@ -1,6 +1,9 @@
 use clap::{Arg, Command};
 use markitup;
-use std::path::PathBuf;
+use markitup::batch;
+use markitup::split;
+use markitup::generator::image2md::ImageProcessingMode;
+use std::path::{Path, PathBuf};
 
 fn main() {
     let matches = Command::new("markitup")
@@ -9,7 +12,7 @@ fn main() {
         .about("A markup conversion tool with AI enhancement capabilities")
         .arg(
             Arg::new("input")
-                .help("Input file path")
+                .help("Input file or directory path")
                 .required(true)
                 .index(1),
         )
@@ -18,7 +21,14 @@ fn main() {
                 .short('o')
                 .long("output")
                 .value_name("PATH")
-                .help("Output file path"),
+                .help("Output file path (output directory when the input is a directory)"),
+        )
+        .arg(
+            Arg::new("profile")
+                .short('p')
+                .long("profile")
+                .value_name("NAME")
+                .help("Apply a [profile.<NAME>] from Config.toml (e.g. web, repo) before the other flags"),
         )
         .arg(
             Arg::new("image-path")
@@ -27,6 +37,19 @@ fn main() {
                 .value_name("PATH")
                 .help("Path for image processing"),
         )
+        .arg(
+            Arg::new("embed-images")
+                .long("embed-images")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["extract-images", "image-path"])
+                .help("Embed images as base64 data URIs"),
+        )
+        .arg(
+            Arg::new("extract-images")
+                .long("extract-images")
+                .action(clap::ArgAction::SetTrue)
+                .help("Save images as files: in --image-path, or <output>_images next to the output file"),
+        )
         .arg(
             Arg::new("ai-enable")
                 .short('a')
@@ -41,13 +64,102 @@ fn main() {
                 .help("Disable AI enhancement features")
                 .conflicts_with("ai-enable"),
         )
+        .arg(
+            Arg::new("incremental")
+                .long("incremental")
+                .action(clap::ArgAction::SetTrue)
+                .help("Skip inputs whose Markdown output is newer or whose content hash is unchanged"),
+        )
+        .arg(
+            Arg::new("stdout")
+                .long("stdout")
+                .visible_alias("tee")
+                .action(clap::ArgAction::SetTrue)
+                .help("Also print the Markdown to stdout when writing to an output file"),
+        )
+        .arg(
+            Arg::new("html")
+                .long("html")
+                .action(clap::ArgAction::SetTrue)
+                .help("Render the result to HTML instead of Markdown"),
+        )
+        .arg(
+            Arg::new("merge")
+                .long("merge")
+                .value_name("FILE")
+                .help("Convert a directory into one combined Markdown file"),
+        )
+        .arg(
+            Arg::new("split-by-heading")
+                .long("split-by-heading")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u8).range(1..=6))
+                .requires("output")
+                .help("Write one file per level-N heading into the --output directory (text before the first one goes to index.md)"),
+        )
+        .arg(
+            Arg::new("extract-images-only")
+                .long("extract-images-only")
+                .action(clap::ArgAction::SetTrue)
+                .requires("image-path")
+                .help("Only write the media embedded in a DOCX/PPTX/XLSX to the image path"),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .action(clap::ArgAction::SetTrue)
+                .help("Suppress status messages (stderr) such as \"Output written to:\" and conversion warnings"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .action(clap::ArgAction::SetTrue)
+                .help("Log debug details to stderr (RUST_LOG takes precedence)"),
+        )
         .get_matches();
 
+    // Before anything touches the settings, so their debug log is captured too
+    let default_level = if matches.get_flag("verbose") { "debug" } else { "warn" };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_level)).init();
+
     let file_path = matches.get_one::<String>("input").unwrap();
 
+    // 库在配置出错时回退到内置默认值；CLI 仍然直接报错退出
+    if let Err(e) = markitup::config::try_settings() {
+        eprintln!("Failed to load configuration: {}", e);
+        std::process::exit(1);
+    }
+
+    // 配置文件 -> profile -> CLI 参数
+    if let Some(profile) = matches.get_one::<String>("profile")
+        && let Err(e) = markitup::config::apply_profile(profile)
+    {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+
     // 收集CLI覆盖参数
-    let image_path_override = matches.get_one::<String>("image-path").map(PathBuf::from);
-    let output_path_override = matches.get_one::<String>("output").map(PathBuf::from);
+    let output_arg = matches.get_one::<String>("output").map(PathBuf::from);
+    let split_level = matches.get_one::<u8>("split-by-heading").map(|&level| level as usize);
+    // With --split-by-heading the output is a directory; image links are resolved from its index.md
+    let output_path_override = match split_level {
+        Some(_) => output_arg.as_ref().map(|dir| dir.join(split::INDEX_FILE_NAME)),
+        None => output_arg.clone(),
+    };
+    let mut image_path_override = matches.get_one::<String>("image-path").map(PathBuf::from);
+    if matches.get_flag("extract-images") {
+        markitup::config::set_image_mode(Some(ImageProcessingMode::SaveToFile));
+        if image_path_override.is_none() {
+            image_path_override = Some(default_image_dir(
+                output_arg.as_deref(),
+                Path::new(file_path).is_dir() || split_level.is_some(),
+            ));
+        }
+    } else if matches.get_flag("embed-images") {
+        markitup::config::set_image_mode(Some(ImageProcessingMode::Base64));
+    }
     let ai_enable_override = if matches.get_flag("ai-enable") {
         Some(true)
     } else if matches.get_flag("no-ai") {
@@ -66,16 +178,107 @@ fn main() {
     // 获取更新后的配置
     let settings = markitup::config::get_settings();
 
-    let output = markitup::convert_from_path(file_path);
+    let incremental = matches.get_flag("incremental");
+    let also_stdout = matches.get_flag("stdout");
+    let quiet = matches.get_flag("quiet");
+    let html = matches.get_flag("html");
+    let input_path = PathBuf::from(file_path);
+
+    if matches.get_flag("extract-images-only") {
+        let result = std::fs::read(&input_path)
+            .map_err(|e| format!("Failed to read file {}: {}", file_path, e))
+            .and_then(|file_stream| {
+                let file = markitup::ConverterFile {
+                    file_path: Some(file_path.clone()),
+                    file_stream,
+                };
+                markitup::extract_images(file).map_err(String::from)
+            });
+        match result {
+            Ok(images) => {
+                if !quiet {
+                    for (name, _) in &images {
+                        eprintln!("Image written to: {}", settings.image_path.join(name).display());
+                    }
+                }
+            }
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(merge_path) = matches.get_one::<String>("merge") {
+        if !input_path.is_dir() {
+            eprintln!("Error: --merge requires a directory input");
+            std::process::exit(1);
+        }
+        if run_merge(&input_path, Path::new(merge_path), quiet, html) > 0 {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let (Some(level), Some(output_dir)) = (split_level, &output_arg) {
+        if input_path.is_dir() {
+            eprintln!("Error: --split-by-heading requires a file input");
+            std::process::exit(1);
+        }
+        match run_split(file_path, output_dir, level, quiet, html) {
+            Ok(written) => {
+                if !quiet {
+                    for path in written {
+                        eprintln!("Output written to: {}", path.display());
+                    }
+                }
+            }
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if input_path.is_dir() {
+        let output_root = settings.output_path.clone().unwrap_or_else(|| input_path.clone());
+        if run_batch(&input_path, &output_root, incremental, quiet, html) > 0 {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(output_path) = &settings.output_path
+        && incremental
+        && batch::output_is_fresh(&input_path, output_path)
+    {
+        if !quiet {
+            eprintln!("Skipped (up to date): {}", output_path.display());
+        }
+        return;
+    }
+
+    let output = convert_with_warnings(file_path, quiet).map(|markdown| {
+        if html {
+            markitup::markdown_to_html(&markdown)
+        } else {
+            markdown
+        }
+    });
     match output {
         Ok(markup) => {
             if let Some(output_path) = &settings.output_path {
-                match std::fs::write(output_path, &markup) {
-                    Ok(_) => println!("Output written to: {}", output_path.display()),
-                    Err(err) => {
-                        eprintln!("Error writing to file: {}", err);
-                        std::process::exit(1);
-                    }
+                if let Err(err) = std::fs::write(output_path, &markup) {
+                    eprintln!("Error writing to file: {}", err);
+                    std::process::exit(1);
+                }
+                if also_stdout {
+                    println!("{}", markup);
+                }
+                if !quiet {
+                    eprintln!("Output written to: {}", output_path.display());
                 }
             } else {
                 println!("{}", markup);
@@ -87,3 +290,218 @@ fn main() {
         }
     }
 }
+
+// 批量转换目录下的所有文件，返回失败的文件数
+fn run_batch(
+    input_root: &Path,
+    output_root: &Path,
+    incremental: bool,
+    quiet: bool,
+    html: bool,
+) -> usize {
+    let inputs = match batch::collect_inputs(input_root) {
+        Ok(inputs) => inputs,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return 1;
+        }
+    };
+
+    let mut manifest = batch::Manifest::load(output_root);
+    let mut failures = 0;
+
+    for input in inputs {
+        let mut output = batch::output_path_for(&input, input_root, output_root);
+        if html {
+            output.set_extension("html");
+        }
+        let key = input
+            .strip_prefix(input_root)
+            .unwrap_or(&input)
+            .to_string_lossy()
+            .into_owned();
+
+        if incremental && batch::output_is_fresh(&input, &output) {
+            if !quiet {
+                eprintln!("Skipped (up to date): {}", input.display());
+            }
+            continue;
+        }
+
+        let file_stream = match std::fs::read(&input) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                eprintln!("Error reading {}: {}", input.display(), err);
+                failures += 1;
+                continue;
+            }
+        };
+
+        let hash = batch::content_hash(&file_stream);
+        if incremental && output.exists() && manifest.is_unchanged(&key, &hash) {
+            if !quiet {
+                eprintln!("Skipped (unchanged): {}", input.display());
+            }
+            continue;
+        }
+
+        // Image links are made relative to the file currently being written
+        markitup::config::update_settings_with_cli_args(None, Some(output.clone()), None);
+
+        let file = markitup::ConverterFile {
+            file_path: Some(input.to_string_lossy().into_owned()),
+            file_stream,
+        };
+        // Duplicate inputs in one run are converted once; a cache hit still writes the
+        // images and repeats the warnings
+        let converted = markitup::convert_cached_detailed(file)
+            .and_then(|conversion| report_conversion(&input.to_string_lossy(), conversion, quiet))
+            .map(|markdown| {
+                if html {
+                    markitup::markdown_to_html(&markdown)
+                } else {
+                    markdown
+                }
+            });
+        let result = converted.map_err(String::from).and_then(|markup| {
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create output directory: {}", e))?;
+            }
+            std::fs::write(&output, &markup).map_err(|e| format!("Error writing to file: {}", e))
+        });
+
+        match result {
+            Ok(_) => {
+                if !quiet {
+                    eprintln!("Output written to: {}", output.display());
+                }
+                manifest.record(key, hash);
+            }
+            Err(err) => {
+                eprintln!("Error converting {}: {}", input.display(), err);
+                failures += 1;
+            }
+        }
+    }
+
+    if let Err(err) = manifest.save() {
+        eprintln!("Error: {}", err);
+    }
+
+    failures
+}
+
+// 合并模式：按排序后的顺序转换目录下所有文件，拼接成一个文档，返回失败的文件数
+fn run_merge(input_root: &Path, merge_path: &Path, quiet: bool, html: bool) -> usize {
+    let inputs = match batch::collect_inputs(input_root) {
+        Ok(inputs) => inputs,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return 1;
+        }
+    };
+
+    let base_image_path = markitup::config::get_settings().image_path;
+    let mut sections = Vec::new();
+    let mut failures = 0;
+
+    for input in inputs {
+        // Saved images go into one sub-directory per source so names can't collide
+        let image_path = if base_image_path.as_os_str().is_empty() {
+            None
+        } else {
+            Some(base_image_path.join(batch::image_namespace(&input, input_root)))
+        };
+        markitup::config::update_settings_with_cli_args(
+            image_path,
+            Some(merge_path.to_path_buf()),
+            None,
+        );
+
+        match convert_with_warnings(&input.to_string_lossy(), quiet) {
+            Ok(markdown) => {
+                let name = input.strip_prefix(input_root).unwrap_or(&input);
+                sections.push(format!("# {}\n\n{}", name.display(), markdown.trim()));
+                if !quiet {
+                    eprintln!("Converted: {}", input.display());
+                }
+            }
+            Err(err) => {
+                eprintln!("Error converting {}: {}", input.display(), err);
+                failures += 1;
+            }
+        }
+    }
+
+    let mut merged = sections.join("\n\n---\n\n");
+    merged.push('\n');
+    if html {
+        merged = markitup::markdown_to_html(&merged);
+    }
+
+    if let Err(err) = std::fs::write(merge_path, &merged) {
+        eprintln!("Error writing to file: {}", err);
+        return failures + 1;
+    }
+    if !quiet {
+        eprintln!("Output written to: {}", merge_path.display());
+    }
+
+    failures
+}
+
+// 转换单个文件并按 N 级标题拆分写入目录，返回写入的文件
+fn run_split(
+    file_path: &str,
+    output_dir: &Path,
+    level: usize,
+    quiet: bool,
+    html: bool,
+) -> Result<Vec<PathBuf>, markitup::ConvertError> {
+    let markdown = convert_with_warnings(file_path, quiet)?;
+    let mut sections = split::split_by_heading(&markdown, level);
+    if html {
+        for section in &mut sections {
+            section.markdown = markitup::markdown_to_html(&section.markdown);
+            section.file_name = Path::new(&section.file_name).with_extension("html").to_string_lossy().into_owned();
+        }
+    }
+    split::write_sections(output_dir, &sections)
+}
+
+// Convert a file, write its images and report what the conversion had to guess or drop
+fn convert_with_warnings(file_path: &str, quiet: bool) -> Result<String, markitup::ConvertError> {
+    let conversion = markitup::convert_from_path_detailed(file_path)?;
+    report_conversion(file_path, conversion, quiet)
+}
+
+// Write a finished conversion's images and print its warnings
+fn report_conversion(
+    file_path: &str,
+    conversion: markitup::Conversion,
+    quiet: bool,
+) -> Result<String, markitup::ConvertError> {
+    let image_path = markitup::config::get_settings().image_path;
+    markitup::write_images(&image_path, &conversion.images)?;
+
+    if !quiet {
+        for warning in &conversion.warnings {
+            eprintln!("Warning: {}: {}", file_path, warning);
+        }
+    }
+    Ok(conversion.markdown)
+}
+
+// --extract-images without --image-path: `<stem>_images/` beside the output file,
+// `images/` inside an output directory, `./images` when writing to stdout
+fn default_image_dir(output: Option<&Path>, input_is_dir: bool) -> PathBuf {
+    match output {
+        Some(output) if input_is_dir => output.join("images"),
+        Some(output) => {
+            let stem = output.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+            output.with_file_name(format!("{}_images", stem))
+        }
+        None => PathBuf::from("images"),
+    }
+}
@@ -37,6 +37,11 @@ impl Default for RightPanelMode{
     }
 }
 enum WorkerMessage {
+    // 大文件先发送截断的预览，完整结果稍后以 ConversionResult 发送
+    Preview {
+        full_markdown: String,
+        display_markdown: String,
+    },
     ConversionResult {
         full_markdown: String,   // 完整的 Markdown 内容
         display_markdown: String, // 经过 Base64 替换后的 Markdown 内容，用于编辑器显示
@@ -44,11 +49,24 @@ enum WorkerMessage {
     Error(String), // 转换过程中发生的错误
 }
 
+// 超过此大小的文件先显示预览
+const PREVIEW_THRESHOLD_BYTES: usize = 1024 * 1024;
+const PREVIEW_CHARS: usize = 20_000;
+
 fn replace_base64_in_markdown(markdown:&str) ->String{
     let re = Regex::new(r"\((data:image/[^;]+;base64,[^)]+)\)").unwrap();
     re.replace_all(markdown, "(base64_image_placeholder)").into_owned()
 }
 
+// (background, text) 颜色
+fn theme_colors(dark_mode: bool) -> (egui::Color32, egui::Color32) {
+    if dark_mode {
+        (egui::Color32::from_rgb(27, 27, 27), egui::Color32::WHITE)
+    } else {
+        (egui::Color32::from_rgb(248, 248, 248), egui::Color32::from_rgb(30, 30, 30))
+    }
+}
+
 pub struct UIFramework{
     show_config_panel:bool,
     show_help_panel:bool,
@@ -66,6 +84,7 @@ pub struct UIFramework{
     pub font_size_body:f32,
     pub background_color: egui::Color32,
     pub text_color: egui::Color32,
+    pub dark_mode: bool,
     
     //convert state
     convert_state: Arc<Mutex<ConvertState>>,
@@ -83,6 +102,9 @@ impl Default for UIFramework{
     
     fn default()->Self{
         let (tx, rx) = unbounded();
+        let settings = config::get_settings();
+        let dark_mode = settings.dark_mode;
+        let (background_color, text_color) = theme_colors(dark_mode);
         Self{
             show_config_panel:false,
             show_help_panel:false,
@@ -95,10 +117,11 @@ impl Default for UIFramework{
             right_panel_mode: RightPanelMode::default(),
             markdown_cache: egui_commonmark::CommonMarkCache::default(),
 
-            font_size_heading:25.0,
-            font_size_body:18.0,
-            background_color:egui::Color32::from_rgb(27, 27, 27),
-            text_color: egui::Color32::WHITE,
+            font_size_heading: settings.font_size_heading,
+            font_size_body: settings.font_size_body,
+            background_color,
+            text_color,
+            dark_mode,
             convert_state: Arc::new(Mutex::new(ConvertState::Idle)), 
             egui_ctx: egui::Context::default(),
 
@@ -119,6 +142,11 @@ impl eframe::App for UIFramework{
         while let Ok(msg) = self.worker_receiver.try_recv() {
             let mut state_guard = self.convert_state.lock().unwrap(); // 获取转换状态的锁
             match msg {
+                WorkerMessage::Preview { full_markdown, display_markdown } => {
+                    // 状态保持 Converting，完整结果到达后再替换
+                    self.current_markdown_content = full_markdown;
+                    self.editor_display_content = display_markdown;
+                }
                 WorkerMessage::ConversionResult { full_markdown, display_markdown } => {
                     // 如果收到了成功转换的消息
                     self.current_markdown_content = full_markdown; // 更新完整 Markdown 内容
@@ -142,7 +170,7 @@ impl eframe::App for UIFramework{
                     });
 
                     if ui.button("config").clicked() {
-                        println!("点击了 config 按钮");
+                        log::debug!("点击了 config 按钮");
                         self.show_config_panel=!self.show_config_panel;
                         self.show_help_panel=false;
                     }
@@ -202,7 +230,7 @@ impl eframe::App for UIFramework{
                         // 在遍历结束后，从后往前删除元素以避免索引问题
                         for &idx in indices_to_remove.iter().rev() {
                             let removed_path = self.file_list.remove(idx);
-                            println!("Removed file from list: {:?}", removed_path.file_name().unwrap_or_default());
+                            log::info!("Removed file from list: {:?}", removed_path.file_name().unwrap_or_default());
 
                             // 如果被移除的是当前选中的文件，则清除相关状态
                             if self.select_file_path.as_ref() == Some(&removed_path) {
@@ -247,7 +275,7 @@ impl eframe::App for UIFramework{
                                 RightPanelMode::Preview => RightPanelMode::Editor,
                                 RightPanelMode::Editor => RightPanelMode::Preview,
                             };
-                            println!("当前模式: {:?}", self.right_panel_mode);
+                            log::debug!("当前模式: {:?}", self.right_panel_mode);
                         }
                         ui.add_space(10.0); // 按钮之间的间距
                         if ui.button("Save Markdown").clicked(){
@@ -278,6 +306,8 @@ impl eframe::App for UIFramework{
 
        }); //end central panel
 
+        let mut theme_changed = false;
+        let mut font_size_changed = false;
         if self.show_config_panel{
         egui::Window::new("config")
             .open(&mut self.show_config_panel)
@@ -317,6 +347,22 @@ impl eframe::App for UIFramework{
                         // 第二个选项
                         ui.radio_value(&mut self.config_choice, true, "Yes");
                     });
+                    ui.add_space(5.0);
+
+                    ui.horizontal(|ui| {
+                        ui.label("Theme");
+                        theme_changed |= ui.radio_value(&mut self.dark_mode, true, "Dark").changed();
+                        theme_changed |= ui.radio_value(&mut self.dark_mode, false, "Light").changed();
+                    });
+                    ui.add_space(5.0);
+
+                    // --- 字号 ---
+                    font_size_changed |= ui
+                        .add(egui::Slider::new(&mut self.font_size_heading, 12.0..=48.0).text("Heading size"))
+                        .changed();
+                    font_size_changed |= ui
+                        .add(egui::Slider::new(&mut self.font_size_body, 10.0..=36.0).text("Body size"))
+                        .changed();
                     ui.add_space(10.0);
 
                     ui.separator();
@@ -330,6 +376,17 @@ impl eframe::App for UIFramework{
             });
         });
         }
+        if theme_changed {
+            (self.background_color, self.text_color) = theme_colors(self.dark_mode);
+            self.apply_theme(ctx);
+            config::set_dark_mode(self.dark_mode);
+            // 预览缓存里的代码高亮等按旧主题生成，需要重建
+            self.markdown_cache = egui_commonmark::CommonMarkCache::default();
+        }
+        if font_size_changed {
+            self.apply_text_styles(ctx);
+            config::set_font_sizes(self.font_size_heading, self.font_size_body);
+        }
         if self.show_help_panel{
             egui::Window::new("help")
                 .open(&mut self.show_help_panel)
@@ -377,25 +434,52 @@ impl UIFramework{
                 .unwrap()
                 .insert(0, "my_custom_font".to_owned());
         cc.egui_ctx.set_fonts(fonts);
-        let mut style = (*cc.egui_ctx.style()).clone();
-        style.text_styles.insert(egui::TextStyle::Button, egui::FontId::proportional(app.font_size_heading)); // 使用标题字号作为按钮字号
-        style.text_styles.insert(egui::TextStyle::Body, egui::FontId::proportional(app.font_size_body));
-        style.text_styles.insert(egui::TextStyle::Heading, egui::FontId::proportional(app.font_size_heading));
+        app.apply_text_styles(&cc.egui_ctx);
 
         // 设置颜色
-        style.visuals.window_fill = app.background_color;
-        style.visuals.panel_fill = app.background_color;
-        //style.visuals.text_color = app.text_color; // 默认文本颜色
-
-        cc.egui_ctx.set_style(style);
+        app.apply_theme(&cc.egui_ctx);
 
         cc.egui_ctx.set_pixels_per_point(1.2);
         app
     }
 
+    // 按当前字号更新 TextStyle
+    fn apply_text_styles(&self, ctx: &egui::Context) {
+        let mut style = (*ctx.style()).clone();
+        style.text_styles.insert(egui::TextStyle::Button, egui::FontId::proportional(self.font_size_heading)); // 使用标题字号作为按钮字号
+        style.text_styles.insert(egui::TextStyle::Body, egui::FontId::proportional(self.font_size_body));
+        style.text_styles.insert(egui::TextStyle::Heading, egui::FontId::proportional(self.font_size_heading));
+        ctx.set_style(style);
+    }
+
+    // 按当前主题替换 visuals，字号等其它样式保持不变
+    fn apply_theme(&self, ctx: &egui::Context) {
+        let mut style = (*ctx.style()).clone();
+        style.visuals = if self.dark_mode {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        };
+        style.visuals.window_fill = self.background_color;
+        style.visuals.panel_fill = self.background_color;
+        style.visuals.override_text_color = Some(self.text_color);
+        ctx.set_style(style);
+    }
+
     fn open_files_dialog(&mut self) {
-        let result = FileDialog::new()
+        let formats = markitup::supported_formats();
+        let all_extensions: Vec<&str> = formats
+            .iter()
+            .flat_map(|format| format.extensions.iter().copied())
+            .collect();
+
+        let mut dialog = FileDialog::new()
             .set_title("Select files")
+            .add_filter("All Supported", &all_extensions);
+        for format in formats {
+            dialog = dialog.add_filter(format.name, format.extensions);
+        }
+        let result = dialog
             .add_filter("All Files", &["*"])
             .pick_files(); // This call is blocking
 
@@ -403,11 +487,11 @@ impl UIFramework{
             for path_buf in paths {
                 if !self.file_list.contains(&path_buf) { // Avoid duplicates
                     self.file_list.push(path_buf.clone());
-                    println!("Added file: {:?}", path_buf);
+                    log::info!("Added file: {:?}", path_buf);
                 }
             }
         } else {
-            println!("File selection canceled");
+            log::debug!("File selection canceled");
         }
     }
     fn save_markdown_content(&self){
@@ -425,14 +509,14 @@ impl UIFramework{
 
             if let Some(save_path) = file_dialog_result {
                 match std::fs::write(&save_path, &self.current_markdown_content) {
-                    Ok(_) => println!("Markdown 已成功保存到: {:?}", save_path),
-                    Err(e) => eprintln!("保存 Markdown 失败: {}", e),
+                    Ok(_) => log::info!("Markdown 已成功保存到: {:?}", save_path),
+                    Err(e) => log::error!("保存 Markdown 失败: {}", e),
                 }
             } else {
-                println!("保存操作已取消。");
+                log::debug!("保存操作已取消。");
             }
         } else {
-            println!("没有文件被选中，无法保存内容。");
+            log::warn!("没有文件被选中，无法保存内容。");
             // add ui?
         }
     }
@@ -456,8 +540,30 @@ impl UIFramework{
         thread::spawn(move || {
             // 尝试将 PathBuf 转换为 &str，如果失败则返回错误
             let result = if let Some(path_str) = path_for_thread.to_str() {
-                // 调用您的 markitup 库进行转换
-                markitup::convert_from_path(path_str)
+                // 调用您的 markitup 库进行转换；重新选中同一文件时直接命中缓存
+                std::fs::read(path_str)
+                    .map_err(|e| format!("Failed to read file {}: {}", path_str, e))
+                    .and_then(|file_stream| {
+                        if file_stream.len() > PREVIEW_THRESHOLD_BYTES {
+                            let file = markitup::ConverterFile {
+                                file_path: Some(path_str.to_string()),
+                                file_stream: file_stream.clone(),
+                            };
+                            if let Ok(preview) = markitup::convert_preview(file, PREVIEW_CHARS) {
+                                let display_preview = replace_base64_in_markdown(&preview);
+                                sender_for_thread.send(WorkerMessage::Preview {
+                                    full_markdown: preview,
+                                    display_markdown: display_preview,
+                                }).unwrap();
+                                ui_ctx.request_repaint();
+                            }
+                        }
+                        let file = markitup::ConverterFile {
+                            file_path: Some(path_str.to_string()),
+                            file_stream,
+                        };
+                        markitup::convert_cached(file).map_err(|e| e.to_string())
+                    })
             } else {
                 Err(format!("文件路径包含无效的 UTF-8 字符: {}", path_for_thread.display()))
             };
@@ -481,6 +587,8 @@ impl UIFramework{
 }
 
 fn main(){
+    // RUST_LOG=debug for details
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
     createFrame();
 
 }
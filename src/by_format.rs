@@ -0,0 +1,96 @@
+//! One entry point per input format, for callers that already know what they have:
+//! no MIME or extension detection, otherwise the same conversion as `convert`
+//! (current `SETTINGS`, post-processing, SaveToFile images written to `image_path`).
+
+use crate::formats::FormatKind;
+use crate::{check_input_size, convert_kind, save_images, ConvertError};
+
+fn convert_format(bytes: &[u8], kind: FormatKind) -> Result<String, ConvertError> {
+    check_input_size(bytes.len())?;
+    save_images(convert_kind(bytes, kind)?)
+}
+
+/// Convert a Word document (.docx)
+pub fn docx_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Docx)
+}
+
+/// Convert a PowerPoint presentation (.pptx)
+pub fn pptx_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Pptx)
+}
+
+/// Convert an Excel workbook (.xlsx), one table per sheet
+pub fn xlsx_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Xlsx)
+}
+
+/// Convert an OpenDocument text (.odt)
+pub fn odt_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Odt)
+}
+
+/// Convert an OpenDocument spreadsheet (.ods), one table per sheet
+pub fn ods_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Ods)
+}
+
+/// Convert CSV as one table
+pub fn csv_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Csv)
+}
+
+/// Convert JSON
+pub fn json_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Json)
+}
+
+/// Convert XML
+pub fn xml_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Xml)
+}
+
+/// Convert an HTML page
+pub fn html_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Html)
+}
+
+/// Convert an image (PNG, JPEG, SVG...)
+pub fn image_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Image)
+}
+
+/// Convert a WAV recording, transcribed
+pub fn wav_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Wav)
+}
+
+/// Convert audio in any supported container (MP3, FLAC, OGG...), decoded then transcribed
+pub fn audio_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Audio)
+}
+
+/// Convert vCard contacts
+pub fn vcard_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Vcard)
+}
+
+/// Convert an iCalendar file
+pub fn ical_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Ical)
+}
+
+/// Convert an email (.eml) with its attachments
+pub fn eml_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Eml)
+}
+
+/// Convert a ZIP archive: every supported file inside, one section each
+pub fn zip_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Zip)
+}
+
+/// Convert plain text
+pub fn text_to_markdown(bytes: &[u8]) -> Result<String, ConvertError> {
+    convert_format(bytes, FormatKind::Text)
+}
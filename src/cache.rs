@@ -0,0 +1,154 @@
+//! Conversion cache keyed by the SHA-256 of the input bytes plus the serialized
+//! settings. Changing an option (AI, image mode, ...) changes the key, so stale
+//! entries are never returned; they just stop being hit. A `Conversion` is stored
+//! whole, images included, in memory and, when `Settings.cache_dir` is set, on disk too.
+
+use crate::config::SETTINGS;
+use crate::generator::image2md::ImageProcessingMode;
+use crate::Conversion;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+static MEMORY: Lazy<Mutex<HashMap<String, Conversion>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Cache key for an input under the current settings
+pub fn cache_key(file_stream: &[u8], file_path: Option<&str>) -> String {
+    let mut cfg = SETTINGS.read().unwrap().clone();
+    // The extension takes part in type detection, so it is part of the key
+    let extension = file_path
+        .and_then(|path| std::path::Path::new(path).extension())
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    // The input's file name only shows up in the output through `{source_stem}`
+    let source_stem = match &cfg.image_name_template {
+        Some(template) if template.contains("{source_stem}") => file_path
+            .and_then(|path| std::path::Path::new(path).file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned()),
+        _ => None,
+    };
+    // Output location only matters for relative links to saved images
+    if cfg.image_processing_mode() == ImageProcessingMode::Base64 {
+        cfg.output_path = None;
+    }
+    // Every setting is part of the key, including ones added later
+    let settings = serde_json::to_string(&cfg).expect("Settings serialize to JSON");
+
+    let mut hasher = Sha256::new();
+    hasher.update(file_stream);
+    hasher.update(format!("ext={};source_stem={:?};settings={}", extension, source_stem, settings).as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+// On disk: the conversion without its images, which are kept base64-encoded
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    conversion: Conversion,
+    images: Vec<(PathBuf, String)>,
+}
+
+impl DiskEntry {
+    fn new(conversion: &Conversion) -> Self {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let images = conversion
+            .images
+            .iter()
+            .map(|(path, data)| (path.clone(), engine.encode(data)))
+            .collect();
+        let conversion = Conversion { images: Vec::new(), ..conversion.clone() };
+        DiskEntry { conversion, images }
+    }
+
+    fn into_conversion(self) -> Option<Conversion> {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let mut conversion = self.conversion;
+        for (path, encoded) in self.images {
+            conversion.images.push((path, engine.decode(encoded).ok()?));
+        }
+        Some(conversion)
+    }
+}
+
+fn disk_path(key: &str) -> Option<PathBuf> {
+    let cache_dir = SETTINGS.read().unwrap().cache_dir.clone()?;
+    Some(cache_dir.join(format!("{}.json", key)))
+}
+
+pub fn get(key: &str) -> Option<Conversion> {
+    if let Some(conversion) = MEMORY.lock().unwrap().get(key) {
+        return Some(conversion.clone());
+    }
+
+    // An unreadable entry (e.g. from an older format) is a miss
+    let content = fs::read_to_string(disk_path(key)?).ok()?;
+    let conversion = serde_json::from_str::<DiskEntry>(&content).ok()?.into_conversion()?;
+    MEMORY.lock().unwrap().insert(key.to_string(), conversion.clone());
+    Some(conversion)
+}
+
+/// Store a result; a failing disk write only costs the on-disk copy
+pub fn put(key: &str, conversion: &Conversion) {
+    MEMORY.lock().unwrap().insert(key.to_string(), conversion.clone());
+
+    if let Some(path) = disk_path(key) {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(content) = serde_json::to_string(&DiskEntry::new(conversion)) {
+            let _ = fs::write(path, content);
+        }
+    }
+}
+
+/// Drop every cached conversion, in memory and in `cache_dir`
+pub fn clear() {
+    MEMORY.lock().unwrap().clear();
+
+    let cache_dir = SETTINGS.read().unwrap().cache_dir.clone();
+    if let Some(entries) = cache_dir.and_then(|dir| fs::read_dir(dir).ok()) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{with_settings, Settings};
+
+    #[test]
+    fn test_cache_key_covers_every_setting() {
+        let key_with = |settings: Settings| with_settings(settings, || cache_key(b"input", Some("a.docx")));
+        let mut other_model = Settings::builtin();
+        other_model.model_path = PathBuf::from("other-model");
+        assert_eq!(key_with(Settings::builtin()), key_with(Settings::builtin()));
+        assert_ne!(key_with(Settings::builtin()), key_with(other_model));
+    }
+
+    #[test]
+    fn test_disk_entry_keeps_images() {
+        let conversion = Conversion {
+            markdown: "![chart](pic-1.png)".to_string(),
+            word_count: 1,
+            reading_minutes: 0.0,
+            metadata: None,
+            images: vec![(PathBuf::from("pic-1.png"), vec![0x89, b'P', b'N', b'G'])],
+            warnings: vec!["guessed a heading".to_string()],
+            partial: false,
+        };
+        let json = serde_json::to_string(&DiskEntry::new(&conversion)).unwrap();
+        let restored = serde_json::from_str::<DiskEntry>(&json).unwrap().into_conversion().unwrap();
+        assert_eq!(restored.markdown, conversion.markdown);
+        assert_eq!(restored.images, conversion.images);
+        assert_eq!(restored.warnings, conversion.warnings);
+    }
+}
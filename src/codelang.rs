@@ -0,0 +1,109 @@
+//! Language hints for fenced code blocks, shared by every converter that emits code
+
+// Tokens that are fairly specific to one language; SQL is matched case-insensitively
+const LANGUAGE_MARKERS: &[(&str, &[&str])] = &[
+    ("python", &["def ", "elif ", "self.", "__init__", "__name__", "print(", "import ", "None", "True"]),
+    ("rust", &["fn ", "let mut ", "impl ", "pub fn", "use std::", "println!", "&self", "Some(", "Ok(", "-> "]),
+    ("javascript", &["function ", "const ", "=> ", "console.log", "require(", "document.", "===", "let "]),
+    ("go", &["package ", "func ", ":= ", "fmt.", "err != nil", "import ("]),
+    ("java", &["public class ", "public static void", "System.out", "import java", "@Override", "private "]),
+    ("csharp", &["using System", "namespace ", "Console.Write", "{ get; set; }", "public class "]),
+    ("cpp", &["#include <", "std::", "cout", "template<", "template <", "::"]),
+    ("c", &["#include <", "int main(", "printf(", "malloc(", "#include <stdio.h>"]),
+    ("bash", &["echo ", "fi\n", "then\n", "$(", "export ", "done\n", "esac"]),
+    ("sql", &["select ", " from ", "where ", "insert into", "create table", "update ", "join "]),
+    ("html", &["<html", "<div", "<span", "<p>", "</", "<body"]),
+    ("css", &["px;", "color:", "margin:", "padding:", "@media", "display:"]),
+    ("ruby", &["def ", "end\n", "puts ", "require '", " do |", "attr_accessor"]),
+];
+
+/// Guess the fence language (```python, ```rust, ...) of a code snippet.
+/// Conservative: `None` unless a shebang or a clear token majority identifies it.
+pub fn guess_fence_language(code: &str) -> Option<&'static str> {
+    let code = code.trim();
+    if code.is_empty() {
+        return None;
+    }
+
+    let first_line = code.lines().next().unwrap_or_default();
+    if let Some(interpreter) = first_line.strip_prefix("#!") {
+        return shebang_language(interpreter);
+    }
+    if first_line.starts_with("<?php") {
+        return Some("php");
+    }
+    if first_line.starts_with("<?xml") {
+        return Some("xml");
+    }
+    if (code.starts_with('{') || code.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(code).is_ok()
+    {
+        return Some("json");
+    }
+
+    // End with a newline so line-final markers like "fi\n" match the last line too
+    let text = format!("{}\n", code);
+    let lowercase = text.to_lowercase();
+    let mut scores: Vec<(&'static str, usize)> = LANGUAGE_MARKERS
+        .iter()
+        .map(|(language, markers)| {
+            let haystack = if *language == "sql" { &lowercase } else { &text };
+            let score = markers.iter().filter(|marker| haystack.contains(*marker)).count();
+            (*language, score)
+        })
+        .collect();
+    scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+    // At least two distinct markers, and a clear lead over the runner-up
+    match scores.as_slice() {
+        [(language, best), (_, second), ..] if *best >= 2 && *best > *second => Some(language),
+        _ => None,
+    }
+}
+
+fn shebang_language(interpreter: &str) -> Option<&'static str> {
+    // "/usr/bin/env python3", "/bin/bash -e", ...
+    let program = interpreter
+        .split_whitespace()
+        .map(|part| part.rsplit('/').next().unwrap_or(part))
+        .find(|part| *part != "env")?;
+
+    let language = match program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+        "python" => "python",
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => "bash",
+        "node" | "nodejs" => "javascript",
+        "ruby" => "ruby",
+        "perl" => "perl",
+        "php" => "php",
+        _ => return None,
+    };
+    Some(language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guess_fence_language() {
+        assert_eq!(guess_fence_language("#!/usr/bin/env python3\nprint('hi')"), Some("python"));
+        assert_eq!(guess_fence_language("#!/bin/bash\necho hi"), Some("bash"));
+        assert_eq!(
+            guess_fence_language("def greet(name):\n    if name is None:\n        print('hi')"),
+            Some("python")
+        );
+        assert_eq!(
+            guess_fence_language("use std::io;\n\npub fn main() {\n    let mut x = 1;\n    println!(\"{}\", x);\n}"),
+            Some("rust")
+        );
+        assert_eq!(guess_fence_language("SELECT id FROM users WHERE active = 1"), Some("sql"));
+        assert_eq!(guess_fence_language("{\"a\": [1, 2]}"), Some("json"));
+    }
+
+    #[test]
+    fn test_low_confidence_stays_bare() {
+        assert_eq!(guess_fence_language(""), None);
+        assert_eq!(guess_fence_language("just some words"), None);
+        assert_eq!(guess_fence_language("x = 1"), None);
+    }
+}
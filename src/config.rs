@@ -9,40 +9,245 @@
 //! //     println!("{:?}", cfg.model_path);
 //! // }
 
+use crate::generator::docx2md::{RtlMarkup, TrackChangesMode};
+use crate::generator::image2md::{ImageLinkStyle, ImageProcessingMode};
+use crate::markdown::MarkdownFlavor;
+use crate::table::TableStyle;
 use config::{Config, ConfigError, Environment, File, FileFormat};
 use once_cell::sync::Lazy;
-use serde::Deserialize;
-use std::{env, fs, path::PathBuf, sync::RwLock};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::{LockResult, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::{env, fs, path::PathBuf};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Settings {
     pub model_path: PathBuf,
     pub image_path: PathBuf,
+    // base64 or save_to_file; unset: save when image_path is set, otherwise base64
+    pub image_mode: Option<ImageProcessingMode>,
     pub output_path: Option<PathBuf>,
     pub is_ai_enpower: bool,
     pub doubao_api_key: Option<String>,
+    // audio: list this many recognizer hypotheses under the transcript when > 1
+    pub transcription_alternatives: usize,
+    // audio: append a start/end/word timing table to the transcript
+    pub transcription_words: bool,
+    // SVG: emit raw <svg> markup instead of a data URI (base64 mode only)
+    pub inline_svg: bool,
+    // SVG: rasterize to PNG for renderers without vector support
+    pub rasterize_svg: bool,
+    // animated GIF: keep only the first frame (as PNG); the alt text notes the frame count either way
+    pub gif_first_frame_only: bool,
+    // scratch directory for temp files; falls back to the system temp dir
+    pub temp_dir: Option<PathBuf>,
+    // reject inputs larger than this many bytes before reading/processing them
+    pub max_input_bytes: Option<usize>,
+    // ZIP-based inputs: largest uncompressed size allowed per entry / for the whole archive
+    pub max_zip_entry_bytes: u64,
+    pub max_zip_total_bytes: u64,
+    // XLSX/ODS sheets: CSV delimiter as an ASCII code (59 = `;`), default `,`
+    pub csv_delimiter: Option<u8>,
+    // XLSX/ODS sheets: size columns by the first row (`Xlsx2CsvConfig::use_header`)
+    pub csv_use_header: bool,
+    // CSV: rewrite decimal commas in numeric cells ("1.234,56" -> "1234.56")
+    pub normalize_decimals: bool,
+    // DOCX/PPTX: `# <title>` to open the document with; unset uses the document's
+    // own title (core properties) and omits the heading when there is none
+    pub document_title: Option<String>,
+    // DOCX/PPTX: open with the core-properties title as `# <title>`; false leaves the
+    // body's own first heading as the top (an explicit document_title is still used)
+    pub synthetic_title: bool,
+    // PPTX: convert slides hidden in the deck (`show="0"`), marked "(hidden)"
+    pub include_hidden_slides: bool,
+    // DOCX tracked changes: accept_all, reject_all or show_both
+    pub track_changes: TrackChangesMode,
+    // DOCX right-to-left paragraphs: mark (U+200F), html (`<div dir="rtl">`) or off
+    pub rtl_markup: RtlMarkup,
+    // HTML: tag names (e.g. "details", "figure") whose elements are kept as raw HTML
+    pub preserve_raw_html: Vec<String>,
+    // XML: elements nested deeper than this are omitted (with a warning)
+    pub xml_max_depth: usize,
+    // stop generating once the Markdown exceeds this many bytes
+    pub max_output_bytes: Option<usize>,
+    // convert smart quotes, dashes and NBSP to plain ASCII
+    pub normalize_typography: bool,
+    // one blank line between blocks, no trailing whitespace, one final newline
+    pub tidy_blank_lines: bool,
+    // tidy the output for Markdown linters: blank lines around headings/tables,
+    // no trailing whitespace, one final newline
+    pub lint_output: bool,
+    // target dialect: gfm (default) or commonmark; commonmark renders every table
+    // as HTML, struck text as <del> and task lists as checkbox glyphs
+    pub flavor: MarkdownFlavor,
+    // pipe tables only ("markdown"), HTML <table> for multi-line/merged cells ("html"),
+    // or ```csv blocks ("csv", whatever the flavor)
+    pub table_style: TableStyle,
+    // SaveToFile image links: relative to the output file, absolute, or bare file names
+    pub image_link_style: ImageLinkStyle,
+    // SaveToFile image names, e.g. "report-img-{index}"; tokens: {index} {hash8}
+    // {timestamp} {ai} {source_stem}. Unset: AI description or `pic-<timestamp>`
+    pub image_name_template: Option<String>,
+    // written into blank table cells, e.g. `-` or `N/A` (default: nothing)
+    pub empty_cell_placeholder: String,
+    // list item marker: `-`, `*` or `+` (markdownlint MD004)
+    pub bullet_char: char,
+    // dates shown to readers (iCal events, email Date): IANA zone such as
+    // "Europe/Berlin" or "UTC"; unset or "local" uses the system zone
+    pub timezone: Option<String>,
+    // chrono strftime formats for those dates, with and without a time of day
+    pub date_format: String,
+    pub date_only_format: String,
+    // fail on undetectable input instead of guessing a type (text, JPEG)
+    pub strict: bool,
+    // drop every image: no extraction, encoding, saving or AI naming
+    pub skip_images: bool,
+    // emit images as `![alt][imageN]` with the data/paths collected at the end
+    pub reference_images: bool,
+    // on-disk conversion cache (`convert_cached`); memory-only when unset
+    pub cache_dir: Option<PathBuf>,
+    // GUI: dark (default) or light theme
+    pub dark_mode: bool,
+    // GUI: heading/button and body text sizes
+    pub font_size_heading: f32,
+    pub font_size_body: f32,
+    // MIME type to convert as, skipping ALL content/extension detection
+    // (env: APP__FORCE_MIME); for extensionless input arriving on stdin
+    pub force_mime: Option<String>,
 }
 
-pub static SETTINGS: Lazy<RwLock<Settings>> = Lazy::new(|| {
+/// The process-wide settings. Without `init_settings` they are loaded from the config
+/// files and environment on first use; a failure there never aborts the host process:
+/// the built-in defaults are used instead, the error is logged, and `try_settings` reports it.
+pub static SETTINGS: Lazy<SettingsLock> = Lazy::new(|| {
+    if let Some(settings) = PRESET.lock().unwrap().take() {
+        return SettingsLock::new(settings);
+    }
+
     let settings = Settings::new().unwrap_or_else(|e| {
-        eprintln!("Failed to load configuration: {}", e);
-        std::process::exit(1);
+        log::error!("Failed to load configuration, using the built-in defaults: {}", e);
+        *LOAD_ERROR.lock().unwrap() = Some(e.to_string());
+        Settings::builtin()
     });
     
-    // Debug output for all configuration settings
-    if cfg!(debug_assertions) {
-        println!("=== Configuration Settings ===");
-        println!("model_path: {:?}", settings.model_path);
-        println!("image_path: {:?}", settings.image_path);
-        println!("output_path: {:?}", settings.output_path);
-        println!("is_ai_enpower: {}", settings.is_ai_enpower);
-        println!("doubao_api_key: {:?}", settings.doubao_api_key.as_ref());
-        println!("==============================");
-    }
+    settings.log("Configuration settings");
     
-    RwLock::new(settings)
+    SettingsLock::new(settings)
 });
 
+/// The lock behind `SETTINGS`. Reads on a thread running `with_settings` (e.g.
+/// `convert_bytes_as`) see that call's settings; writes always go to the process-wide ones.
+pub struct SettingsLock {
+    global: RwLock<Settings>,
+}
+
+/// What `SettingsLock::read` hands out; use it as a `&Settings`
+pub enum SettingsGuard<'a> {
+    Global(RwLockReadGuard<'a, Settings>),
+    Scoped(Rc<Settings>),
+}
+
+thread_local! {
+    // Settings of the `with_settings` call running on this thread
+    static SCOPED: RefCell<Option<Rc<Settings>>> = const { RefCell::new(None) };
+}
+
+impl SettingsLock {
+    fn new(settings: Settings) -> Self {
+        Self { global: RwLock::new(settings) }
+    }
+
+    pub fn read(&self) -> LockResult<SettingsGuard<'_>> {
+        if let Some(scoped) = SCOPED.with(|scoped| scoped.borrow().clone()) {
+            return Ok(SettingsGuard::Scoped(scoped));
+        }
+        self.global
+            .read()
+            .map(SettingsGuard::Global)
+            .map_err(|e| PoisonError::new(SettingsGuard::Global(e.into_inner())))
+    }
+
+    pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, Settings>> {
+        self.global.write()
+    }
+}
+
+impl Deref for SettingsGuard<'_> {
+    type Target = Settings;
+
+    fn deref(&self) -> &Settings {
+        match self {
+            SettingsGuard::Global(guard) => guard,
+            SettingsGuard::Scoped(settings) => settings,
+        }
+    }
+}
+
+/// Run `f` with `settings` standing in for `SETTINGS` on the current thread; the
+/// previous (outer) settings come back afterwards, also when `f` panics.
+///
+/// The override is thread-local: work `f` hands to another thread (a rayon pool,
+/// tokio's `spawn_blocking` as in `convert_async`) reads the process-wide settings
+/// unless that thread enters `with_settings` itself with a snapshot, e.g.
+/// `let settings = SETTINGS.read().unwrap().clone()` taken before handing off.
+pub(crate) fn with_settings<R>(settings: Settings, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Rc<Settings>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SCOPED.with(|scoped| *scoped.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(SCOPED.with(|scoped| scoped.replace(Some(Rc::new(settings)))));
+    f()
+}
+
+// Handed to `init_settings` before first use; SETTINGS starts from it instead of loading
+static PRESET: Mutex<Option<Settings>> = Mutex::new(None);
+
+// Why SETTINGS fell back to the built-in defaults
+static LOAD_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// The current settings, or the error that made `SETTINGS` fall back to the defaults
+pub fn try_settings() -> Result<Settings, ConfigError> {
+    let settings = get_settings();
+    match LOAD_ERROR.lock().unwrap().clone() {
+        Some(message) => Err(ConfigError::Message(message)),
+        None => Ok(settings),
+    }
+}
+
+/// Configure the crate in code. Before first use no Config.toml or environment is read
+/// at all; afterwards the current settings are replaced. Settings that fail validation
+/// are treated like a broken config file: the built-in defaults are used instead and
+/// `try_settings` reports the error.
+///
+/// ```no_run
+/// use markitup::config::{init_settings, Settings};
+///
+/// init_settings(Settings::from_toml_str("image_mode = \"save_to_file\"\nimage_path = \"out/img\"").unwrap());
+/// ```
+pub fn init_settings(settings: Settings) {
+    let (settings, load_error) = match settings.validate() {
+        Ok(settings) => (settings, None),
+        Err(e) => {
+            log::error!("Invalid settings passed to init_settings, using the built-in defaults: {}", e);
+            (Settings::builtin(), Some(e.to_string()))
+        }
+    };
+    settings.log("Configuration settings (init_settings)");
+    *PRESET.lock().unwrap() = Some(settings);
+    let lock = Lazy::force(&SETTINGS);
+    // Already initialised before this call: replace the contents
+    if let Some(settings) = PRESET.lock().unwrap().take() {
+        *lock.write().unwrap() = settings;
+    }
+    *LOAD_ERROR.lock().unwrap() = load_error;
+}
+
 // 提供一个便捷的访问函数，保持原有的使用方式
 pub fn get_settings() -> Settings {
     SETTINGS.read().unwrap().clone()
@@ -68,20 +273,97 @@ pub fn update_settings_with_cli_args(
         settings.is_ai_enpower = enable;
     }
     
-    // Debug output after CLI updates
-    if cfg!(debug_assertions) {
-        println!("=== Updated Configuration Settings ===");
-        println!("model_path: {:?}", settings.model_path);
-        println!("image_path: {:?}", settings.image_path);
-        println!("output_path: {:?}", settings.output_path);
-        println!("is_ai_enpower: {}", settings.is_ai_enpower);
-        println!("doubao_api_key: {:?}", settings.doubao_api_key.as_ref());
-        println!("=====================================");
-    }
+    settings.log("Updated configuration settings");
+}
+
+// 切换到 Config.toml 中的 `[profile.<name>]`；之后再应用 CLI 参数
+pub fn apply_profile(name: &str) -> Result<(), ConfigError> {
+    let settings = Settings::load(Some(name))?;
+    settings.log(&format!("Configuration settings (profile {})", name));
+    *SETTINGS.write().unwrap() = settings;
+    Ok(())
+}
+
+// 图片处理方式 (CLI --embed-images / --extract-images)
+pub fn set_image_mode(mode: Option<ImageProcessingMode>) {
+    SETTINGS.write().unwrap().image_mode = mode;
+}
+
+// GUI 主题切换
+pub fn set_dark_mode(dark_mode: bool) {
+    SETTINGS.write().unwrap().dark_mode = dark_mode;
+}
+
+// GUI 字号调整
+pub fn set_font_sizes(heading: f32, body: f32) {
+    let mut settings = SETTINGS.write().unwrap();
+    settings.font_size_heading = heading;
+    settings.font_size_body = body;
 }
 
 impl Settings {
+    // Debug log of the main settings; the API key is only reported as set or not
+    fn log(&self, heading: &str) {
+        log::debug!(
+            "{}: model_path={:?} image_path={:?} output_path={:?} is_ai_enpower={} doubao_api_key={}",
+            heading,
+            self.model_path,
+            self.image_path,
+            self.output_path,
+            self.is_ai_enpower,
+            if self.doubao_api_key.is_some() { "<set>" } else { "<unset>" },
+        );
+    }
+
+    /// `image_mode`, or the mode implied by whether `image_path` is set
+    pub fn image_processing_mode(&self) -> ImageProcessingMode {
+        self.image_mode.unwrap_or(if self.image_path.as_os_str().is_empty() {
+            ImageProcessingMode::Base64
+        } else {
+            ImageProcessingMode::SaveToFile
+        })
+    }
+
+    /// `table_style`, except that CommonMark has no pipe tables and gets HTML instead
+    pub fn effective_table_style(&self) -> TableStyle {
+        match (self.flavor, self.table_style) {
+            (_, TableStyle::CsvBlock) | (MarkdownFlavor::Gfm, _) => self.table_style,
+            (MarkdownFlavor::CommonMark, _) => TableStyle::HtmlOnly,
+        }
+    }
+
+    /// Directory used for every temporary file the crate writes
+    pub fn resolved_temp_dir(&self) -> PathBuf {
+        self.temp_dir.clone().unwrap_or_else(env::temp_dir)
+    }
+
     pub fn new() -> Result<Self, ConfigError> {
+        Self::load(None)
+    }
+
+    /// The built-in defaults with `toml` laid over them; no file or environment is read.
+    /// Keys left out keep their defaults, so a partial config is enough.
+    pub fn from_toml_str(toml: &str) -> Result<Self, ConfigError> {
+        let settings: Settings = Config::builder()
+            .add_source(File::from_str(include_str!("../Config.toml"), FileFormat::Toml))
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()?
+            .try_deserialize()?;
+        settings.validate()
+    }
+
+    /// Only the Config.toml compiled into the crate: no external file, profile or environment
+    pub fn builtin() -> Self {
+        Config::builder()
+            .add_source(File::from_str(include_str!("../Config.toml"), FileFormat::Toml))
+            .build()
+            .and_then(Config::try_deserialize)
+            .expect("built-in Config.toml must deserialize")
+    }
+
+    /// Settings with `[profile.<name>]` laid over the config files; environment
+    /// variables still take precedence
+    pub fn load(profile: Option<&str>) -> Result<Self, ConfigError> {
         // 1. built-in default config
         let mut builder = Config::builder()
             .add_source(File::from_str(
@@ -90,21 +372,82 @@ impl Settings {
             ));
 
         // 2. try to load external config file
-        if let Ok(exe_path) = env::current_exe() {
-            if let Some(dir) = exe_path.parent() {
-                let external = dir.join("Config.toml");
-                if fs::metadata(&external).is_ok() {
-                    builder = builder.add_source(
-                        File::with_name(external.to_str().unwrap()).required(false),
-                    );
-                }
+        if let Ok(exe_path) = env::current_exe()
+            && let Some(dir) = exe_path.parent()
+        {
+            let external = dir.join("Config.toml");
+            if fs::metadata(&external).is_ok() {
+                builder = builder.add_source(
+                    File::with_name(external.to_str().unwrap()).required(false),
+                );
             }
         }
 
-        // 3. load environment variables
+        // 3. the selected profile's overrides
+        let files = builder.build()?;
+        let mut builder = Config::builder().add_source(files.clone());
+        if let Some(name) = profile {
+            let table = files
+                .get_table(&format!("profile.{}", name))
+                .map_err(|_| ConfigError::Message(format!("Unknown profile '{}'", name)))?;
+            let mut overrides = Config::builder();
+            for (key, value) in table {
+                overrides = overrides.set_override(key, value)?;
+            }
+            builder = builder.add_source(overrides.build()?);
+        }
+
+        // 4. load environment variables
         builder = builder.add_source(Environment::with_prefix("APP").separator("__"));
 
         // 构建并 Deserialize 到 Settings
-        builder.build()?.try_deserialize()
+        let settings: Settings = builder.build()?.try_deserialize()?;
+        settings.validate()
+    }
+
+    // Checks serde can't express
+    fn validate(self) -> Result<Self, ConfigError> {
+        if !matches!(self.bullet_char, '-' | '*' | '+') {
+            return Err(ConfigError::Message(format!(
+                "bullet_char must be one of '-', '*' or '+', got {:?}",
+                self.bullet_char
+            )));
+        }
+        crate::dates::validate(
+            self.timezone.as_deref(),
+            &[&self.date_format, &self.date_only_format],
+        )
+        .map_err(ConfigError::Message)?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_profiles_override_defaults() {
+        let repo = Settings::load(Some("repo")).unwrap();
+        assert_eq!(repo.image_mode, Some(ImageProcessingMode::SaveToFile));
+        assert_eq!(repo.image_path, PathBuf::from("images"));
+        assert!(repo.lint_output);
+        assert_eq!(Settings::load(Some("web")).unwrap().image_processing_mode(), ImageProcessingMode::Base64);
+        assert!(Settings::load(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_from_toml_str_overrides_defaults() {
+        let settings = Settings::from_toml_str("bullet_char = \"*\"\nflavor = \"commonmark\"").unwrap();
+        assert_eq!(settings.bullet_char, '*');
+        assert_eq!(settings.date_only_format, "%Y-%m-%d");
+        assert!(Settings::from_toml_str("bullet_char = \"x\"").is_err());
+    }
+
+    #[test]
+    fn test_builtin_defaults_load() {
+        let builtin = Settings::builtin();
+        assert_eq!(builtin.bullet_char, '-');
+        assert_eq!(builtin.image_mode, None);
     }
 }
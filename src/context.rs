@@ -0,0 +1,197 @@
+//! Per-conversion state that generators contribute to without threading it
+//! through every `run` signature. `convert_detailed` opens a scope for the
+//! duration of one conversion; outside a scope the helpers are no-ops.
+//! The context is per thread, like `config::with_settings`: code running on a
+//! worker thread (rayon, `spawn_blocking`) sees no scope, so warnings, images
+//! and partial results must be reported from the thread that opened it.
+
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+#[derive(Default)]
+struct ConversionContext {
+    // reference-style image definitions, in first-use order
+    image_refs: Vec<(String, String)>,
+    // content hash of a link target -> its reference id
+    image_ref_ids: HashMap<String, String>,
+    // SaveToFile images handed back with the result instead of written mid-conversion
+    images: Vec<(PathBuf, Vec<u8>)>,
+    // lossy/heuristic decisions and recovered failures (e.g. a broken slide)
+    warnings: Vec<String>,
+    // some part (sheet, slide, picture...) was left out
+    partial: bool,
+    // file stem of the input, for `{source_stem}` in image names
+    source_stem: Option<String>,
+    // images named so far, and the names they got
+    image_count: usize,
+    image_names: HashSet<String>,
+    // `convert_preview`'s byte budget, on top of `Settings.max_output_bytes`
+    output_limit: Option<usize>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<ConversionContext>> = const { RefCell::new(None) };
+}
+
+/// Keeps the context alive until dropped. Nested scopes (e.g. a legacy file
+/// re-entering `convert_detailed`) share the outer one.
+pub(crate) struct Scope {
+    owner: bool,
+    // `isolated` only: the surrounding context, put back on drop
+    outer: Option<ConversionContext>,
+}
+
+impl Scope {
+    pub(crate) fn begin() -> Self {
+        let owner = CONTEXT.with(|context| {
+            let mut context = context.borrow_mut();
+            if context.is_some() {
+                false
+            } else {
+                *context = Some(ConversionContext::default());
+                true
+            }
+        });
+        Scope { owner, outer: None }
+    }
+
+    /// A fresh context for a conversion nested in another one (each file of a merge),
+    /// so its `take_*` calls only drain what it recorded itself. The surrounding
+    /// context comes back on drop; the output budget carries over.
+    pub(crate) fn isolated() -> Self {
+        let outer = CONTEXT.with(|context| {
+            let mut context = context.borrow_mut();
+            let output_limit = context.as_ref().and_then(|outer| outer.output_limit);
+            context.replace(ConversionContext { output_limit, ..ConversionContext::default() })
+        });
+        Scope { owner: true, outer }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        if self.owner {
+            let outer = self.outer.take();
+            CONTEXT.with(|context| *context.borrow_mut() = outer);
+        }
+    }
+}
+
+fn with_context<R>(f: impl FnOnce(&mut ConversionContext) -> R) -> Option<R> {
+    CONTEXT.with(|context| context.borrow_mut().as_mut().map(f))
+}
+
+/// Register `target` as a reference-style image and return its id;
+/// identical targets share one id. None when no conversion is in progress.
+pub(crate) fn image_reference(target: &str) -> Option<String> {
+    let hash = format!("{:x}", Sha256::digest(target.as_bytes()));
+    with_context(|context| {
+        if let Some(id) = context.image_ref_ids.get(&hash) {
+            return id.clone();
+        }
+        let id = format!("image{}", context.image_refs.len() + 1);
+        context.image_refs.push((id.clone(), target.to_string()));
+        context.image_ref_ids.insert(hash, id.clone());
+        id
+    })
+}
+
+/// `[id]: target` lines for every image referenced so far, emptied afterwards
+pub(crate) fn take_image_definitions() -> String {
+    with_context(|context| {
+        context.image_ref_ids.clear();
+        context
+            .image_refs
+            .drain(..)
+            .map(|(id, target)| format!("[{}]: {}\n", id, target))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Queue an image for the caller to persist. False when no conversion is in
+/// progress, in which case the caller writes it itself.
+pub(crate) fn defer_image(path: PathBuf, bytes: &[u8]) -> bool {
+    with_context(|context| context.images.push((path, bytes.to_vec()))).is_some()
+}
+
+/// Images queued by `defer_image` so far, emptied afterwards
+pub(crate) fn take_images() -> Vec<(PathBuf, Vec<u8>)> {
+    with_context(|context| std::mem::take(&mut context.images)).unwrap_or_default()
+}
+
+/// Record a warning for `Conversion::warnings`; dropped outside a conversion
+pub(crate) fn warn(message: impl Into<String>) {
+    let message = message.into();
+    with_context(|context| context.warnings.push(message));
+}
+
+/// Warnings recorded by `warn` so far, emptied afterwards
+pub(crate) fn take_warnings() -> Vec<String> {
+    with_context(|context| std::mem::take(&mut context.warnings)).unwrap_or_default()
+}
+
+/// A part of the document (sheet, slide, picture...) could not be converted and was
+/// left out: recorded as a warning and reported as `Conversion::partial`
+pub(crate) fn part_failed(message: impl Into<String>) {
+    warn(message);
+    mark_partial();
+}
+
+pub(crate) fn mark_partial() {
+    with_context(|context| context.partial = true);
+}
+
+/// Whether a part was left out so far; reset afterwards
+pub(crate) fn take_partial() -> bool {
+    with_context(|context| std::mem::take(&mut context.partial)).unwrap_or_default()
+}
+
+/// Remember the input's file stem; the outermost conversion's wins
+pub(crate) fn set_source_stem(file_path: Option<&str>) {
+    let stem = file_path
+        .and_then(|path| std::path::Path::new(path).file_stem())
+        .map(|stem| stem.to_string_lossy().into_owned());
+    with_context(|context| {
+        if context.source_stem.is_none() {
+            context.source_stem = stem;
+        }
+    });
+}
+
+pub(crate) fn source_stem() -> Option<String> {
+    with_context(|context| context.source_stem.clone()).flatten()
+}
+
+/// Cap the output of the conversion in progress
+pub(crate) fn set_output_limit(limit: Option<usize>) {
+    with_context(|context| context.output_limit = limit);
+}
+
+pub(crate) fn output_limit() -> Option<usize> {
+    with_context(|context| context.output_limit).flatten()
+}
+
+/// 1-based position of the next image in this conversion
+pub(crate) fn next_image_index() -> Option<usize> {
+    with_context(|context| {
+        context.image_count += 1;
+        context.image_count
+    })
+}
+
+/// `name`, or `name-2`, `name-3`... if an earlier image of this conversion has it
+pub(crate) fn claim_image_name(name: String) -> String {
+    with_context(|context| {
+        let mut candidate = name.clone();
+        let mut suffix = 1;
+        while !context.image_names.insert(candidate.clone()) {
+            suffix += 1;
+            candidate = format!("{}-{}", name, suffix);
+        }
+        candidate
+    })
+    .unwrap_or(name)
+}
@@ -0,0 +1,50 @@
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, DynamicImage, ImageOutputFormat};
+use std::io::Cursor;
+
+/// Number of frames in a GIF; 0 when it can't be decoded
+pub fn frame_count(gif_bytes: &[u8]) -> usize {
+    match GifDecoder::new(Cursor::new(gif_bytes)) {
+        Ok(decoder) => decoder.into_frames().take_while(Result::is_ok).count(),
+        Err(_) => 0,
+    }
+}
+
+/// The first frame of a (possibly animated) GIF as PNG
+pub fn first_frame_png(gif_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let decoder = GifDecoder::new(Cursor::new(gif_bytes))
+        .map_err(|e| format!("Failed to decode GIF: {}", e))?;
+    let frame = decoder
+        .into_frames()
+        .next()
+        .ok_or_else(|| "GIF has no frames".to_string())?
+        .map_err(|e| format!("Failed to decode GIF frame: {}", e))?;
+
+    let mut png = Vec::new();
+    DynamicImage::ImageRgba8(frame.into_buffer())
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Frame, Rgba, RgbaImage};
+
+    #[test]
+    fn test_animated_gif_frames() {
+        let mut gif = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut gif);
+            let frames = [Rgba([255, 0, 0, 255]), Rgba([0, 0, 255, 255])]
+                .map(|color| Frame::new(RgbaImage::from_pixel(2, 2, color)));
+            encoder.encode_frames(frames).unwrap();
+        }
+
+        assert_eq!(frame_count(&gif), 2);
+        assert!(first_frame_png(&gif).unwrap().starts_with(b"\x89PNG"));
+        assert_eq!(frame_count(b"not a gif"), 0);
+    }
+}
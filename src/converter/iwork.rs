@@ -0,0 +1,26 @@
+use std::io::Cursor;
+use zip::ZipArchive;
+
+/// Apple iWork packages (.pages/.numbers/.key) are ZIPs of snappy-compressed
+/// protobuf `Index/*.iwa` files; older bundles carry an `Index.zip` instead.
+/// We can't read either, but they must not be mistaken for generic ZIPs.
+pub fn is_iwork(data: &[u8]) -> bool {
+    let Ok(archive) = ZipArchive::new(Cursor::new(data)) else {
+        return false;
+    };
+    archive.file_names().any(|name| {
+        name == "Index.zip"
+            || name.ends_with("/Index.zip")
+            || (name.starts_with("Index/") && name.ends_with(".iwa"))
+    })
+}
+
+/// User-facing description of an iWork package, with the export that works instead
+pub fn describe(extension: &str) -> &'static str {
+    match extension {
+        "pages" => "Apple iWork Pages document (.pages); export it to Word (.docx) from Pages",
+        "numbers" => "Apple iWork Numbers spreadsheet (.numbers); export it to Excel (.xlsx) from Numbers",
+        "key" => "Apple iWork Keynote presentation (.key); export it to PowerPoint (.pptx) from Keynote",
+        _ => "Apple iWork document; export it to an Office format from the iWork app",
+    }
+}
@@ -1,2 +1,12 @@
+//! Format-to-format helpers (XLSX/ODS -> CSV, audio -> WAV, SVG/GIF -> PNG, legacy
+//! Office -> OOXML, ...). Nothing here produces Markdown; the `generator` modules
+//! do that, often on top of these.
+
 pub mod xlsx2csv;
-pub mod audio2wav;
\ No newline at end of file
+pub mod xlsx_media;
+pub mod audio2wav;
+pub mod svg2png;
+pub mod gif2png;
+pub mod ole2ooxml;
+pub mod iwork;
+pub mod ods2csv;pub mod omml2latex;
@@ -0,0 +1,241 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::io::Cursor;
+use zip::ZipArchive;
+use crate::converter::xlsx2csv::{Xlsx2CsvConfig, Xlsx2CsvResult};
+use crate::opc;
+
+type Table = (String, Vec<Vec<String>>);
+
+/// Convert ods byte data to CSV strings, one per table, in the same shape as `xlsx_to_csv`
+pub fn ods_to_csv(data: &[u8], config: Option<Xlsx2CsvConfig>) -> Result<Xlsx2CsvResult, String> {
+    let config = config.unwrap_or_default();
+
+    let mut archive = ZipArchive::new(Cursor::new(data))
+        .map_err(|e| format!("Failed to open ODS archive: {}", e))?;
+    let content = opc::read_part_to_string(&mut archive, "content.xml")?
+        .ok_or_else(|| "Missing content.xml in ODS archive".to_string())?;
+
+    let tables = parse_tables(&content)?;
+    if tables.is_empty() {
+        return Err("No sheets found in ods file".to_string());
+    }
+
+    let mut sheet_names = Vec::new();
+    let mut csv_data = Vec::new();
+    for (name, rows) in tables {
+        csv_data.push(
+            rows_to_csv(&rows, &config)
+                .map_err(|e| format!("Failed to convert sheet '{}': {}", name, e))?,
+        );
+        sheet_names.push(name);
+    }
+
+    Ok(Xlsx2CsvResult {
+        sheet_names,
+        csv_data,
+        failed_sheets: Vec::new(),
+    })
+}
+
+// Current cell: its text and how many columns it spans via `number-columns-repeated`
+struct Cell {
+    text: String,
+    repeat: usize,
+    paragraphs: usize,
+}
+
+/// Read every `table:table` in content.xml.
+///
+/// ODS compresses runs of identical cells/rows with `number-columns-repeated` /
+/// `number-rows-repeated`, and pads every sheet to the full grid (16384 columns,
+/// ~1M rows) with repeated *empty* cells. Repeats are expanded only when content
+/// follows them, so trailing padding never materializes.
+fn parse_tables(xml_content: &str) -> Result<Vec<Table>, String> {
+    let mut reader = Reader::from_str(xml_content);
+    let mut buf = Vec::new();
+    let mut skip_buf = Vec::new();
+
+    let mut tables = Vec::new();
+    let mut table: Option<Table> = None;
+    let mut pending_rows = 0usize;
+    let mut row = Vec::new();
+    let mut row_repeat = 1usize;
+    let mut pending_cells = 0usize;
+    let mut cell: Option<Cell> = None;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => match element.name().as_ref() {
+                b"table:table" => {
+                    let name = opc::attribute_value(&element, b"table:name")?
+                        .unwrap_or_else(|| format!("Sheet{}", tables.len() + 1));
+                    table = Some((name, Vec::new()));
+                    pending_rows = 0;
+                }
+                b"table:table-row" => {
+                    row_repeat = repeat_count(&element, b"table:number-rows-repeated")?;
+                    row.clear();
+                    pending_cells = 0;
+                }
+                b"table:table-cell" | b"table:covered-table-cell" => {
+                    cell = Some(Cell {
+                        text: String::new(),
+                        repeat: repeat_count(&element, b"table:number-columns-repeated")?,
+                        paragraphs: 0,
+                    });
+                }
+                b"text:p" => {
+                    if let Some(cell) = cell.as_mut() {
+                        if cell.paragraphs > 0 {
+                            cell.text.push('\n');
+                        }
+                        cell.paragraphs += 1;
+                    }
+                }
+                // Comments attached to a cell aren't part of its value
+                b"office:annotation" => {
+                    reader
+                        .read_to_end_into(element.name(), &mut skip_buf)
+                        .map_err(|e| format!("Error parsing ODS content: {}", e))?;
+                    skip_buf.clear();
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(element)) => match element.name().as_ref() {
+                b"table:table-row" => {
+                    pending_rows += repeat_count(&element, b"table:number-rows-repeated")?;
+                }
+                b"table:table-cell" | b"table:covered-table-cell" => {
+                    pending_cells += repeat_count(&element, b"table:number-columns-repeated")?;
+                }
+                b"text:s" => {
+                    if let Some(cell) = cell.as_mut() {
+                        let count = opc::attribute_value(&element, b"text:c")?
+                            .and_then(|count| count.parse::<usize>().ok())
+                            .unwrap_or(1);
+                        cell.text.push_str(&" ".repeat(count));
+                    }
+                }
+                b"text:tab" => {
+                    if let Some(cell) = cell.as_mut() {
+                        cell.text.push('\t');
+                    }
+                }
+                b"text:line-break" => {
+                    if let Some(cell) = cell.as_mut() {
+                        cell.text.push('\n');
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(text)) => {
+                if let Some(cell) = cell.as_mut() {
+                    cell.text.push_str(&text.unescape().unwrap_or_default());
+                }
+            }
+            Ok(Event::End(element)) => match element.name().as_ref() {
+                b"table:table-cell" | b"table:covered-table-cell" => {
+                    if let Some(cell) = cell.take() {
+                        if cell.text.is_empty() {
+                            pending_cells += cell.repeat;
+                        } else {
+                            row.extend(std::iter::repeat_n(String::new(), pending_cells));
+                            pending_cells = 0;
+                            row.extend(std::iter::repeat_n(cell.text, cell.repeat));
+                        }
+                    }
+                }
+                b"table:table-row" => {
+                    if let Some((_, rows)) = table.as_mut() {
+                        if row.is_empty() {
+                            pending_rows += row_repeat;
+                        } else {
+                            rows.extend(std::iter::repeat_n(Vec::new(), pending_rows));
+                            pending_rows = 0;
+                            rows.extend(std::iter::repeat_n(row.clone(), row_repeat));
+                        }
+                    }
+                }
+                b"table:table" => {
+                    if let Some(table) = table.take() {
+                        tables.push(table);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Error parsing ODS content: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(tables)
+}
+
+fn repeat_count(element: &quick_xml::events::BytesStart, key: &[u8]) -> Result<usize, String> {
+    Ok(opc::attribute_value(element, key)?
+        .and_then(|count| count.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1))
+}
+
+// Rows are padded to the widest one so every CSV record has the same length
+fn rows_to_csv(rows: &[Vec<String>], config: &Xlsx2CsvConfig) -> Result<String, String> {
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    let mut output = Vec::new();
+    {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(config.delimiter)
+            .from_writer(&mut output);
+
+        for row in rows {
+            let mut record = row.clone();
+            record.resize(width, String::new());
+            writer.write_record(&record)
+                .map_err(|e| format!("Failed to write row: {}", e))?;
+        }
+
+        writer.flush()
+            .map_err(|e| format!("Failed to flush writer: {}", e))?;
+    }
+
+    String::from_utf8(output)
+        .map_err(|e| format!("Failed to convert to UTF-8: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_cells_and_rows() {
+        let xml = r#"<office:document-content><office:body><office:spreadsheet>
+            <table:table table:name="Data">
+              <table:table-row>
+                <table:table-cell><text:p>a</text:p></table:table-cell>
+                <table:table-cell table:number-columns-repeated="2"/>
+                <table:table-cell table:number-columns-repeated="2"><text:p>x</text:p></table:table-cell>
+                <table:table-cell table:number-columns-repeated="16379"/>
+              </table:table-row>
+              <table:table-row table:number-rows-repeated="2"><table:table-cell table:number-columns-repeated="16384"/></table:table-row>
+              <table:table-row><table:table-cell><text:p>b</text:p></table:table-cell></table:table-row>
+              <table:table-row table:number-rows-repeated="1048572"><table:table-cell table:number-columns-repeated="16384"/></table:table-row>
+            </table:table>
+        </office:spreadsheet></office:body></office:document-content>"#;
+
+        let tables = parse_tables(xml).unwrap();
+        assert_eq!(tables.len(), 1);
+        let (name, rows) = &tables[0];
+        assert_eq!(name, "Data");
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], vec!["a", "", "", "x", "x"]);
+        assert!(rows[1].is_empty() && rows[2].is_empty());
+        assert_eq!(rows[3], vec!["b"]);
+
+        let csv = rows_to_csv(rows, &Xlsx2CsvConfig::default()).unwrap();
+        assert_eq!(csv, "a,,,x,x\n,,,,\n,,,,\nb,,,,\n");
+    }
+}
@@ -0,0 +1,72 @@
+use std::fs;
+use std::process::Command;
+use crate::config::SETTINGS;
+
+/// Signature of OLE2 compound files, shared by .doc/.xls/.ppt
+const OLE2_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+pub fn is_ole2(data: &[u8]) -> bool {
+    data.starts_with(&OLE2_MAGIC)
+}
+
+/// Modern Office extension for a legacy binary one
+pub fn modern_extension(legacy_extension: &str) -> Option<&'static str> {
+    match legacy_extension {
+        "doc" => Some("docx"),
+        "xls" => Some("xlsx"),
+        "ppt" => Some("pptx"),
+        _ => None,
+    }
+}
+
+pub fn is_libreoffice_available() -> bool {
+    Command::new("soffice")
+        .arg("--version")
+        .output()
+        .is_ok()
+}
+
+/// Convert a legacy .doc/.xls/.ppt to .docx/.xlsx/.pptx with headless LibreOffice
+pub fn convert_with_libreoffice(data: &[u8], legacy_extension: &str) -> Result<Vec<u8>, String> {
+    let modern = modern_extension(legacy_extension)
+        .ok_or_else(|| format!("No modern equivalent for .{}", legacy_extension))?;
+
+    // LibreOffice names its output after the input, so use a private directory
+    let temp_dir = SETTINGS.read().unwrap().resolved_temp_dir();
+    let work_dir = temp_dir.join(format!("markitup_legacy_{}", std::process::id()));
+    fs::create_dir_all(&work_dir)
+        .map_err(|e| format!("Failed to create temporary directory: {}", e))?;
+
+    let input_path = work_dir.join(format!("input.{}", legacy_extension));
+    let output_path = work_dir.join(format!("input.{}", modern));
+
+    let result = fs::write(&input_path, data)
+        .map_err(|e| format!("Failed to write temporary file: {}", e))
+        .and_then(|_| {
+            Command::new("soffice")
+                .arg("--headless")
+                .arg("--convert-to")
+                .arg(modern)
+                .arg("--outdir")
+                .arg(&work_dir)
+                .arg(&input_path)
+                .output()
+                .map_err(|e| format!("Failed to execute LibreOffice: {}", e))
+        })
+        .and_then(|output| {
+            if output.status.success() {
+                fs::read(&output_path)
+                    .map_err(|e| format!("Failed to read LibreOffice output: {}", e))
+            } else {
+                Err(format!(
+                    "LibreOffice execution failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                ))
+            }
+        });
+
+    // Clean up temporary files
+    let _ = fs::remove_dir_all(&work_dir);
+
+    result
+}
@@ -0,0 +1,364 @@
+//! Office Math (OMML, the `m:oMath` elements of a DOCX) -> LaTeX. Covers the common
+//! structures: fractions, sub/superscripts, radicals, n-ary operators (sums, integrals),
+//! delimiters, functions, accents, bars, braces and matrices. Anything else is an error
+//! naming the element, so the caller can fall back to a placeholder.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// One OMML element with its `m:val` (set on property elements such as `m:chr`),
+/// child elements and run text
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MathNode {
+    name: String,
+    val: Option<String>,
+    children: Vec<MathNode>,
+    text: String,
+}
+
+/// Read the element opened by `start` (e.g. `m:oMath`) up to its end tag
+pub fn read_math(reader: &mut Reader<&[u8]>, start: &BytesStart) -> MathNode {
+    let mut stack = vec![math_node(start)];
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) => stack.push(math_node(&element)),
+            Ok(Event::Empty(element)) => stack.last_mut().unwrap().children.push(math_node(&element)),
+            Ok(Event::Text(text)) => stack.last_mut().unwrap().text.push_str(&text.unescape().unwrap_or_default()),
+            Ok(Event::End(_)) => {
+                let node = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => return node,
+                }
+            }
+            // Truncated XML: keep what was read
+            Ok(Event::Eof) | Err(_) => {
+                while stack.len() > 1 {
+                    let node = stack.pop().unwrap();
+                    stack.last_mut().unwrap().children.push(node);
+                }
+                return stack.pop().unwrap();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn math_node(element: &BytesStart) -> MathNode {
+    MathNode {
+        name: String::from_utf8_lossy(element.name().as_ref()).into_owned(),
+        val: crate::opc::attribute_value(element, b"m:val").ok().flatten(),
+        ..MathNode::default()
+    }
+}
+
+/// LaTeX for an `m:oMath` (or any OMML element), without the `$` delimiters;
+/// Err names the first element that has no LaTeX equivalent here
+pub fn to_latex(node: &MathNode) -> Result<String, String> {
+    Ok(latex(node)?.trim().to_string())
+}
+
+impl MathNode {
+    fn child(&self, name: &str) -> Option<&MathNode> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    // `m:val` of `<m:{pr}><m:{property} m:val=".."/></m:{pr}>`
+    fn property(&self, pr: &str, property: &str) -> Option<&str> {
+        self.child(pr)?.child(property)?.val.as_deref()
+    }
+}
+
+fn latex(node: &MathNode) -> Result<String, String> {
+    let arg = |name: &str| node.child(name).map(latex).transpose().map(Option::unwrap_or_default);
+    Ok(match node.name.as_str() {
+        // Containers: their content in order, properties skipped
+        "m:oMath" | "m:oMathPara" | "m:e" | "m:num" | "m:den" | "m:sub" | "m:sup" | "m:deg" | "m:lim"
+        | "m:fName" | "m:box" | "m:phant" => children(node)?,
+        "m:r" => node.child("m:t").map(|t| run_text(&t.text)).unwrap_or_default(),
+        "m:f" => match node.property("m:fPr", "m:type") {
+            Some("lin") => format!("{}/{}", arg("m:num")?, arg("m:den")?),
+            Some("noBar") => format!("\\genfrac{{}}{{}}{{0pt}}{{}}{{{}}}{{{}}}", arg("m:num")?, arg("m:den")?),
+            _ => format!("\\frac{{{}}}{{{}}}", arg("m:num")?, arg("m:den")?),
+        },
+        "m:sSup" => format!("{{{}}}^{{{}}}", arg("m:e")?, arg("m:sup")?),
+        "m:sSub" => format!("{{{}}}_{{{}}}", arg("m:e")?, arg("m:sub")?),
+        "m:sSubSup" => format!("{{{}}}_{{{}}}^{{{}}}", arg("m:e")?, arg("m:sub")?, arg("m:sup")?),
+        "m:sPre" => format!("{{}}_{{{}}}^{{{}}}{{{}}}", arg("m:sub")?, arg("m:sup")?, arg("m:e")?),
+        "m:rad" => match arg("m:deg")? {
+            degree if degree.trim().is_empty() => format!("\\sqrt{{{}}}", arg("m:e")?),
+            degree => format!("\\sqrt[{}]{{{}}}", degree, arg("m:e")?),
+        },
+        "m:nary" => {
+            // No m:chr means an integral
+            let operator = match node.property("m:naryPr", "m:chr").unwrap_or("\u{222B}") {
+                "\u{2211}" => "\\sum",
+                "\u{220F}" => "\\prod",
+                "\u{2210}" => "\\coprod",
+                "\u{222B}" => "\\int",
+                "\u{222C}" => "\\iint",
+                "\u{222D}" => "\\iiint",
+                "\u{222E}" => "\\oint",
+                "\u{22C3}" => "\\bigcup",
+                "\u{22C2}" => "\\bigcap",
+                "\u{22C1}" => "\\bigvee",
+                "\u{22C0}" => "\\bigwedge",
+                other => return Err(format!("n-ary operator {:?}", other)),
+            };
+            let mut out = operator.to_string();
+            let (sub, sup) = (arg("m:sub")?, arg("m:sup")?);
+            if !sub.trim().is_empty() {
+                out.push_str(&format!("_{{{}}}", sub));
+            }
+            if !sup.trim().is_empty() {
+                out.push_str(&format!("^{{{}}}", sup));
+            }
+            format!("{} {}", out, arg("m:e")?)
+        }
+        "m:d" => {
+            let open = node.property("m:dPr", "m:begChr").unwrap_or("(");
+            let close = node.property("m:dPr", "m:endChr").unwrap_or(")");
+            let separator = node.property("m:dPr", "m:sepChr").unwrap_or("|");
+            let items = node
+                .children
+                .iter()
+                .filter(|child| child.name == "m:e")
+                .map(latex)
+                .collect::<Result<Vec<_>, _>>()?;
+            format!("\\left{} {} \\right{}", delimiter(open), items.join(&run_text(separator)), delimiter(close))
+        }
+        "m:func" => format!("{}{{{}}}", arg("m:fName")?, arg("m:e")?),
+        "m:limLow" => format!("{}_{{{}}}", arg("m:e")?, arg("m:lim")?),
+        "m:limUpp" => format!("{}^{{{}}}", arg("m:e")?, arg("m:lim")?),
+        "m:acc" => {
+            // No m:chr means a circumflex
+            let accent = match node.property("m:accPr", "m:chr").unwrap_or("\u{0302}") {
+                "\u{0300}" => "\\grave",
+                "\u{0301}" => "\\acute",
+                "\u{0302}" => "\\hat",
+                "\u{0303}" => "\\tilde",
+                "\u{0304}" | "\u{0305}" | "\u{00AF}" => "\\bar",
+                "\u{0306}" => "\\breve",
+                "\u{0307}" => "\\dot",
+                "\u{0308}" => "\\ddot",
+                "\u{030C}" => "\\check",
+                "\u{20D7}" | "\u{2192}" => "\\vec",
+                other => return Err(format!("accent {:?}", other)),
+            };
+            format!("{}{{{}}}", accent, arg("m:e")?)
+        }
+        "m:bar" => match node.property("m:barPr", "m:pos") {
+            Some("top") => format!("\\overline{{{}}}", arg("m:e")?),
+            _ => format!("\\underline{{{}}}", arg("m:e")?),
+        },
+        "m:borderBox" => format!("\\boxed{{{}}}", arg("m:e")?),
+        "m:groupChr" => match node.property("m:groupChrPr", "m:chr").unwrap_or("\u{23DF}") {
+            "\u{23DF}" => format!("\\underbrace{{{}}}", arg("m:e")?),
+            "\u{23DE}" => format!("\\overbrace{{{}}}", arg("m:e")?),
+            other => return Err(format!("grouping character {:?}", other)),
+        },
+        "m:m" => {
+            let rows = node
+                .children
+                .iter()
+                .filter(|child| child.name == "m:mr")
+                .map(|row| {
+                    row.children
+                        .iter()
+                        .filter(|cell| cell.name == "m:e")
+                        .map(latex)
+                        .collect::<Result<Vec<_>, _>>()
+                        .map(|cells| cells.join(" & "))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            format!("\\begin{{matrix}} {} \\end{{matrix}}", rows.join(" \\\\ "))
+        }
+        "m:eqArr" => {
+            let rows = node
+                .children
+                .iter()
+                .filter(|child| child.name == "m:e")
+                .map(latex)
+                .collect::<Result<Vec<_>, _>>()?;
+            format!("\\begin{{aligned}} {} \\end{{aligned}}", rows.join(" \\\\ "))
+        }
+        // Formatting of the element itself (m:fPr, m:ctrlPr, w:rPr...)
+        name if name.ends_with("Pr") => String::new(),
+        name => return Err(name.to_string()),
+    })
+}
+
+fn children(node: &MathNode) -> Result<String, String> {
+    node.children.iter().map(latex).collect()
+}
+
+// Function names Word writes as plain runs
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "lim", "max", "min", "sup", "inf", "det", "gcd", "deg", "dim", "ker", "arg",
+];
+
+fn run_text(text: &str) -> String {
+    if FUNCTIONS.contains(&text.trim()) {
+        return format!("\\{} ", text.trim());
+    }
+    text.chars().map(symbol).collect()
+}
+
+fn symbol(c: char) -> String {
+    let command = match c {
+        '{' | '}' | '%' | '#' | '&' | '$' | '_' => return format!("\\{}", c),
+        '\\' => "\\backslash",
+        'α' => "\\alpha",
+        'β' => "\\beta",
+        'γ' => "\\gamma",
+        'δ' => "\\delta",
+        'ϵ' => "\\epsilon",
+        'ε' => "\\varepsilon",
+        'ζ' => "\\zeta",
+        'η' => "\\eta",
+        'θ' => "\\theta",
+        'ϑ' => "\\vartheta",
+        'ι' => "\\iota",
+        'κ' => "\\kappa",
+        'λ' => "\\lambda",
+        'μ' => "\\mu",
+        'ν' => "\\nu",
+        'ξ' => "\\xi",
+        'π' => "\\pi",
+        'ρ' => "\\rho",
+        'σ' => "\\sigma",
+        'τ' => "\\tau",
+        'υ' => "\\upsilon",
+        'φ' => "\\varphi",
+        'ϕ' => "\\phi",
+        'χ' => "\\chi",
+        'ψ' => "\\psi",
+        'ω' => "\\omega",
+        'Γ' => "\\Gamma",
+        'Δ' => "\\Delta",
+        'Θ' => "\\Theta",
+        'Λ' => "\\Lambda",
+        'Ξ' => "\\Xi",
+        'Π' => "\\Pi",
+        'Σ' => "\\Sigma",
+        'Φ' => "\\Phi",
+        'Ψ' => "\\Psi",
+        'Ω' => "\\Omega",
+        '±' => "\\pm",
+        '∓' => "\\mp",
+        '×' => "\\times",
+        '÷' => "\\div",
+        '·' | '⋅' => "\\cdot",
+        '−' => "-",
+        '∗' => "*",
+        '≤' => "\\leq",
+        '≥' => "\\geq",
+        '≠' => "\\neq",
+        '≈' => "\\approx",
+        '≡' => "\\equiv",
+        '∼' => "\\sim",
+        '≅' => "\\cong",
+        '∝' => "\\propto",
+        '≪' => "\\ll",
+        '≫' => "\\gg",
+        '∞' => "\\infty",
+        '∂' => "\\partial",
+        '∇' => "\\nabla",
+        '∀' => "\\forall",
+        '∃' => "\\exists",
+        '∅' => "\\emptyset",
+        '∈' => "\\in",
+        '∉' => "\\notin",
+        '⊂' => "\\subset",
+        '⊆' => "\\subseteq",
+        '⊃' => "\\supset",
+        '⊇' => "\\supseteq",
+        '∪' => "\\cup",
+        '∩' => "\\cap",
+        '∧' => "\\wedge",
+        '∨' => "\\vee",
+        '¬' => "\\neg",
+        '→' => "\\to",
+        '←' => "\\leftarrow",
+        '↔' => "\\leftrightarrow",
+        '⇒' => "\\Rightarrow",
+        '⇐' => "\\Leftarrow",
+        '⇔' => "\\Leftrightarrow",
+        '↦' => "\\mapsto",
+        '…' => "\\ldots",
+        '⋯' => "\\cdots",
+        '⋮' => "\\vdots",
+        '⋱' => "\\ddots",
+        '∠' => "\\angle",
+        '⊥' => "\\perp",
+        '∥' => "\\parallel",
+        '°' => "^\\circ",
+        '′' => "'",
+        '″' => "''",
+        'ℏ' => "\\hbar",
+        'ℓ' => "\\ell",
+        'ℝ' => "\\mathbb{R}",
+        'ℕ' => "\\mathbb{N}",
+        'ℤ' => "\\mathbb{Z}",
+        'ℚ' => "\\mathbb{Q}",
+        'ℂ' => "\\mathbb{C}",
+        '∑' => "\\sum",
+        '∏' => "\\prod",
+        '∫' => "\\int",
+        '√' => "\\surd",
+        _ => return c.to_string(),
+    };
+    // A space keeps the command apart from a following letter
+    format!("{} ", command)
+}
+
+// `m:begChr`/`m:endChr` for \left/\right; an empty one is the invisible `.`
+fn delimiter(chr: &str) -> &str {
+    match chr {
+        "" => ".",
+        "{" => "\\{",
+        "}" => "\\}",
+        "\u{2016}" => "\\|",
+        "\u{27E8}" | "\u{2329}" => "\\langle",
+        "\u{27E9}" | "\u{232A}" => "\\rangle",
+        "\u{230A}" => "\\lfloor",
+        "\u{230B}" => "\\rfloor",
+        "\u{2308}" => "\\lceil",
+        "\u{2309}" => "\\rceil",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(xml: &str) -> Result<String, String> {
+        let mut reader = Reader::from_str(xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(element)) => return to_latex(&read_math(&mut reader, &element)),
+                Ok(Event::Eof) | Err(_) => return Err("no element".to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_fraction_scripts_and_symbols() {
+        let xml = r#"<m:oMath><m:f><m:num><m:r><m:t>a+b</m:t></m:r></m:num><m:den><m:sSup><m:e><m:r><m:t>x</m:t></m:r></m:e><m:sup><m:r><m:t>2</m:t></m:r></m:sup></m:sSup></m:den></m:f><m:r><m:t>≤π</m:t></m:r></m:oMath>"#;
+        assert_eq!(convert(xml).unwrap(), "\\frac{a+b}{{x}^{2}}\\leq \\pi");
+    }
+
+    #[test]
+    fn test_sum_delimiters_and_functions() {
+        let xml = r#"<m:oMath><m:nary><m:naryPr><m:chr m:val="∑"/></m:naryPr><m:sub><m:r><m:t>i=1</m:t></m:r></m:sub><m:sup><m:r><m:t>n</m:t></m:r></m:sup><m:e><m:d><m:e><m:func><m:fName><m:r><m:t>sin</m:t></m:r></m:fName><m:e><m:r><m:t>θ</m:t></m:r></m:e></m:func></m:e></m:d></m:e></m:nary></m:oMath>"#;
+        assert_eq!(convert(xml).unwrap(), "\\sum_{i=1}^{n} \\left( \\sin {\\theta } \\right)");
+    }
+
+    #[test]
+    fn test_unsupported_element_is_named() {
+        assert_eq!(convert("<m:oMath><m:foo/></m:oMath>"), Err("m:foo".to_string()));
+    }
+}
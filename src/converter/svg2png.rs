@@ -0,0 +1,18 @@
+use resvg::{tiny_skia, usvg};
+
+/// Rasterize SVG bytes to PNG at the SVG's intrinsic size.
+/// Used for Markdown renderers that can't display vector images.
+pub fn svg_to_png(svg_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let tree = usvg::Tree::from_data(svg_bytes, &usvg::Options::default())
+        .map_err(|e| format!("Failed to parse SVG: {}", e))?;
+
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| "SVG has an empty canvas".to_string())?;
+
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    pixmap
+        .encode_png()
+        .map_err(|e| format!("Failed to encode PNG: {}", e))
+}
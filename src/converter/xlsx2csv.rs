@@ -1,6 +1,10 @@
 use ooxml;
+use rayon::prelude::*;
+use crate::config::{self, SETTINGS};
+use std::io::Write;
 
 /// Configuration for xlsx to csv conversion
+#[derive(Debug, Clone)]
 pub struct Xlsx2CsvConfig {
     /// Delimiter for CSV output (default: comma)
     pub delimiter: u8,
@@ -17,12 +21,40 @@ impl Default for Xlsx2CsvConfig {
     }
 }
 
+impl Xlsx2CsvConfig {
+    /// Builder entry point, e.g. `Xlsx2CsvConfig::builder().delimiter(b';').use_header(true)`
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn use_header(mut self, use_header: bool) -> Self {
+        self.use_header = use_header;
+        self
+    }
+}
+
 /// Result of xlsx to csv conversion
 pub struct Xlsx2CsvResult {
     /// Sheet names in order
     pub sheet_names: Vec<String>,
     /// CSV content for each sheet
     pub csv_data: Vec<String>,
+    /// Sheets that could not be converted; they are left out of the two lists above
+    pub failed_sheets: Vec<FailedSheet>,
+}
+
+/// A sheet whose conversion failed while the rest of the workbook succeeded
+#[derive(Debug, Clone)]
+pub struct FailedSheet {
+    /// Position of the sheet in the workbook
+    pub index: usize,
+    pub name: String,
+    pub error: String,
 }
 
 impl Xlsx2CsvResult {
@@ -44,39 +76,59 @@ impl Xlsx2CsvResult {
 pub fn xlsx_to_csv(data: &[u8], config: Option<Xlsx2CsvConfig>) -> Result<Xlsx2CsvResult, String> {
     let config = config.unwrap_or_default();
     
-    // Write to temporary file since ooxml doesn't support reading from cursor
-    let temp_dir = std::env::temp_dir();
-    let temp_file = temp_dir.join(format!("temp_xlsx_{}.xlsx", std::process::id()));
-    
-    std::fs::write(&temp_file, data)
+    // Write to temporary file since ooxml doesn't support reading from cursor;
+    // a unique name per call, removed when `temp_file` is dropped
+    let settings = SETTINGS.read().unwrap().clone();
+    let mut temp_file = tempfile::Builder::new()
+        .prefix("markitup-")
+        .suffix(".xlsx")
+        .tempfile_in(settings.resolved_temp_dir())
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    temp_file
+        .write_all(data)
+        .and_then(|_| temp_file.flush())
         .map_err(|e| format!("Failed to write temp file: {}", e))?;
     
-    let xlsx = ooxml::document::SpreadsheetDocument::open(&temp_file)
+    let xlsx = ooxml::document::SpreadsheetDocument::open(temp_file.path())
         .map_err(|e| format!("Failed to open xlsx: {}", e))?;
         
     let workbook = xlsx.get_workbook();
     let sheet_names = workbook.worksheet_names();
     
     if sheet_names.is_empty() {
-        let _ = std::fs::remove_file(&temp_file);
         return Err("No sheets found in xlsx file".to_string());
     }
     
-    let mut csv_data = Vec::new();
+    // Sheets are independent, so convert them in parallel; an indexed
+    // par_iter collects back in sheet order. The workers are rayon threads,
+    // so they get the caller's settings explicitly (see `config::with_settings`).
+    let results = sheet_names
+        .par_iter()
+        .map(|sheet_name| {
+            config::with_settings(settings.clone(), || worksheet_to_csv_string(workbook, sheet_name, &config))
+        })
+        .collect::<Vec<_>>();
     
-    for sheet_name in &sheet_names {
-        let csv_string = worksheet_to_csv_string(&workbook, sheet_name, &config)
-            .map_err(|e| format!("Failed to convert sheet '{}': {}", sheet_name, e))?;
-        csv_data.push(csv_string);
+    // A failing sheet is reported, not fatal, unless no sheet converts at all
+    let mut result = Xlsx2CsvResult {
+        sheet_names: Vec::new(),
+        csv_data: Vec::new(),
+        failed_sheets: Vec::new(),
+    };
+    for (index, (name, csv)) in sheet_names.into_iter().zip(results).enumerate() {
+        match csv {
+            Ok(csv) => {
+                result.sheet_names.push(name);
+                result.csv_data.push(csv);
+            }
+            Err(error) => result.failed_sheets.push(FailedSheet { index, name, error }),
+        }
+    }
+    if let (true, Some(failed)) = (result.csv_data.is_empty(), result.failed_sheets.first()) {
+        return Err(format!("Failed to convert sheet '{}': {}", failed.name, failed.error));
     }
     
-    // Clean up temp file
-    let _ = std::fs::remove_file(&temp_file);
-    
-    Ok(Xlsx2CsvResult {
-        sheet_names,
-        csv_data,
-    })
+    Ok(result)
 }
 
 /// Convert a single worksheet to CSV string
@@ -171,4 +223,11 @@ mod tests {
         assert_eq!(config.delimiter, b',');
         assert_eq!(config.use_header, false);
     }
+
+    #[test]
+    fn test_config_builder() {
+        let config = Xlsx2CsvConfig::builder().delimiter(b';').use_header(true);
+        assert_eq!(config.delimiter, b';');
+        assert!(config.use_header);
+    }
 }
@@ -0,0 +1,152 @@
+use crate::config::SETTINGS;
+use crate::opc;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+use zip::ZipArchive;
+
+/// A graphic anchored on a worksheet's drawing layer
+pub enum SheetGraphic {
+    /// Embedded picture: media part name and its bytes
+    Image { name: String, data: Vec<u8> },
+    /// Chart part; charts can't be rendered, so only the title is kept
+    Chart { name: String, title: Option<String> },
+}
+
+/// Collect the pictures and charts of every sheet, keyed by sheet name.
+/// Follows workbook.xml -> sheetN.xml -> drawingN.xml -> media/charts relationships.
+pub fn sheet_graphics(data: &[u8]) -> Result<HashMap<String, Vec<SheetGraphic>>, String> {
+    let mut archive = ZipArchive::new(Cursor::new(data))
+        .map_err(|e| format!("Failed to open XLSX archive: {}", e))?;
+
+    let workbook = opc::read_part_to_string(&mut archive, "xl/workbook.xml")?
+        .ok_or_else(|| "Missing xl/workbook.xml".to_string())?;
+    let workbook_rels = opc::read_rels(&mut archive, "xl/workbook.xml")?;
+
+    let mut result = HashMap::new();
+    for (sheet_name, rel_id) in parse_sheet_list(&workbook)? {
+        let Some(rel) = workbook_rels.get(&rel_id) else {
+            continue;
+        };
+        let sheet_part = opc::resolve_target("xl/workbook.xml", &rel.target);
+
+        let mut graphics = Vec::new();
+        let sheet_rels = opc::read_rels(&mut archive, &sheet_part)?;
+        for rel in sheet_rels.values().filter(|rel| rel.rel_type.ends_with("/drawing")) {
+            let drawing_part = opc::resolve_target(&sheet_part, &rel.target);
+            graphics.extend(read_drawing(&mut archive, &drawing_part)?);
+        }
+
+        if !graphics.is_empty() {
+            result.insert(sheet_name, graphics);
+        }
+    }
+
+    Ok(result)
+}
+
+// (sheet name, relationship id) pairs in workbook order
+fn parse_sheet_list(workbook_xml: &str) -> Result<Vec<(String, String)>, String> {
+    let mut reader = Reader::from_str(workbook_xml);
+    let mut sheets = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) | Ok(Event::Empty(element))
+                if element.name().as_ref() == b"sheet" =>
+            {
+                let name = opc::attribute_value(&element, b"name")?;
+                let rel_id = opc::attribute_value(&element, b"r:id")?;
+                if let (Some(name), Some(rel_id)) = (name, rel_id) {
+                    sheets.push((name, rel_id));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Error parsing workbook XML: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(sheets)
+}
+
+fn read_drawing<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    drawing_part: &str,
+) -> Result<Vec<SheetGraphic>, String> {
+    let Some(content) = opc::read_part_to_string(archive, drawing_part)? else {
+        return Ok(Vec::new());
+    };
+    let rels = opc::read_rels(archive, drawing_part)?;
+    let skip_images = SETTINGS.read().unwrap().skip_images;
+
+    let mut reader = Reader::from_str(&content);
+    let mut graphics = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) | Ok(Event::Empty(element)) => {
+                match element.name().as_ref() {
+                    b"a:blip" if !skip_images => {
+                        let target = opc::attribute_value(&element, b"r:embed")?
+                            .and_then(|id| rels.get(&id))
+                            .map(|rel| opc::resolve_target(drawing_part, &rel.target));
+                        if let Some(name) = target
+                            && let Some(data) = opc::read_part_bytes(archive, &name)?
+                        {
+                            graphics.push(SheetGraphic::Image { name, data });
+                        }
+                    }
+                    b"c:chart" => {
+                        let target = opc::attribute_value(&element, b"r:id")?
+                            .and_then(|id| rels.get(&id))
+                            .map(|rel| opc::resolve_target(drawing_part, &rel.target));
+                        if let Some(name) = target {
+                            let title = match opc::read_part_to_string(archive, &name)? {
+                                Some(chart_xml) => chart_title(&chart_xml)?,
+                                None => None,
+                            };
+                            graphics.push(SheetGraphic::Chart { name, title });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Error parsing drawing XML: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(graphics)
+}
+
+// Text of the chart's <c:title>, if it has a literal one
+fn chart_title(chart_xml: &str) -> Result<Option<String>, String> {
+    let mut reader = Reader::from_str(chart_xml);
+    let mut title = String::new();
+    let mut in_title = false;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) if element.name().as_ref() == b"c:title" => in_title = true,
+            Ok(Event::End(element)) if element.name().as_ref() == b"c:title" => break,
+            Ok(Event::Text(text)) if in_title => {
+                title.push_str(&text.unescape().unwrap_or_default());
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Error parsing chart XML: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    let title = title.trim();
+    Ok(if title.is_empty() { None } else { Some(title.to_string()) })
+}
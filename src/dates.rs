@@ -0,0 +1,79 @@
+//! Human-facing dates in the configured zone and format (`Settings.timezone`,
+//! `Settings.date_format`, `Settings.date_only_format`). Image file names keep
+//! epoch values; only text meant for readers goes through here.
+
+use crate::config::SETTINGS;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+
+/// `time` in the configured zone, written with `date_format`
+pub fn format_timestamp(time: DateTime<Utc>) -> String {
+    let date_format = SETTINGS.read().unwrap().date_format.clone();
+    in_configured_zone(time).format(&date_format).to_string()
+}
+
+/// A wall-clock time with no zone attached (e.g. an iCal "floating" time), written with `date_format`
+pub fn format_naive(time: NaiveDateTime) -> String {
+    let date_format = SETTINGS.read().unwrap().date_format.clone();
+    time.format(&date_format).to_string()
+}
+
+/// A calendar day (all-day events), written with `date_only_format`
+pub fn format_date(date: NaiveDate) -> String {
+    let date_only_format = SETTINGS.read().unwrap().date_only_format.clone();
+    date.format(&date_only_format).to_string()
+}
+
+/// Wall-clock time of `time` in `Settings.timezone`
+pub fn in_configured_zone(time: DateTime<Utc>) -> NaiveDateTime {
+    let timezone = SETTINGS.read().unwrap().timezone.clone();
+    in_zone(time, timezone.as_deref())
+}
+
+// Unset or "local": the system zone
+fn in_zone(time: DateTime<Utc>, timezone: Option<&str>) -> NaiveDateTime {
+    match timezone.and_then(|name| name.trim().parse::<Tz>().ok()) {
+        Some(tz) => time.with_timezone(&tz).naive_local(),
+        None => time.with_timezone(&Local).naive_local(),
+    }
+}
+
+/// Setting checks for `Settings::load`: chrono panics on bad format strings at print time
+pub(crate) fn validate(timezone: Option<&str>, formats: &[&str]) -> Result<(), String> {
+    let named_zone = timezone
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && !name.eq_ignore_ascii_case("local"));
+    if let Some(name) = named_zone.filter(|name| name.parse::<Tz>().is_err()) {
+        return Err(format!(
+            "Unknown timezone {:?} (use an IANA name such as \"Europe/Berlin\", \"UTC\" or \"local\")",
+            name
+        ));
+    }
+    for format in formats {
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            return Err(format!("Invalid date format {:?}", format));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_zone_conversion() {
+        let time = Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap();
+        assert_eq!(in_zone(time, Some("Asia/Shanghai")).to_string(), "2024-07-01 20:00:00");
+        assert_eq!(in_zone(time, Some("UTC")).to_string(), "2024-07-01 12:00:00");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(Some("local"), &["%d.%m.%Y %H:%M"]).is_ok());
+        assert!(validate(Some("Mars/Olympus"), &[]).is_err());
+        assert!(validate(None, &["%Y-%Q"]).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+/// Errors returned by the top-level conversion entry points
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvertError {
+    /// Neither content sniffing nor the file extension identified the input
+    UnknownType,
+    /// The input was identified but no converter handles it
+    UnsupportedType(String),
+    /// Pre-2007 OLE2 compound file (.doc/.xls/.ppt) that must be converted first
+    LegacyBinaryFormat(String),
+    /// The input exceeds `Settings.max_input_bytes`
+    TooLarge { size: usize, limit: usize },
+    /// A ZIP entry (`entry`) or the whole archive (`None`) declares more uncompressed
+    /// data than `Settings.max_zip_entry_bytes` / `max_zip_total_bytes`
+    ArchiveTooLarge { entry: Option<String>, size: u64, limit: u64 },
+    /// The input could not be read
+    Io(String),
+    /// A converter failed while processing the input
+    Conversion(String),
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::UnknownType => write!(f, "Could not determine file type"),
+            ConvertError::UnsupportedType(kind) => write!(f, "Unsupported file type: {}", kind),
+            ConvertError::LegacyBinaryFormat(kind) => write!(
+                f,
+                "{} is a legacy binary format; save it as a modern Office file \
+                 (.docx/.xlsx/.pptx) or install LibreOffice to convert it automatically",
+                kind
+            ),
+            ConvertError::TooLarge { size, limit } => write!(
+                f,
+                "Input is {} bytes, which exceeds the {} byte limit",
+                size, limit
+            ),
+            ConvertError::ArchiveTooLarge { entry: Some(entry), size, limit } => write!(
+                f,
+                "Archive entry {} expands to {} bytes, which exceeds the {} byte limit",
+                entry, size, limit
+            ),
+            ConvertError::ArchiveTooLarge { entry: None, size, limit } => write!(
+                f,
+                "Archive expands to {} bytes, which exceeds the {} byte limit",
+                size, limit
+            ),
+            ConvertError::Io(msg) => write!(f, "{}", msg),
+            ConvertError::Conversion(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+impl From<String> for ConvertError {
+    fn from(msg: String) -> Self {
+        ConvertError::Conversion(msg)
+    }
+}
+
+impl From<ConvertError> for String {
+    fn from(err: ConvertError) -> Self {
+        err.to_string()
+    }
+}
@@ -0,0 +1,208 @@
+//! Table of supported input formats
+//! Single source of truth for extension-based detection, the `convert`
+//! dispatch, and UI file filters.
+
+/// Which converter handles a format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatKind {
+    Wav,
+    Audio,
+    Docx,
+    Pptx,
+    Xlsx,
+    Csv,
+    Json,
+    Xml,
+    Html,
+    Image,
+    Odt,
+    Ods,
+    Vcard,
+    Ical,
+    Eml,
+    Zip,
+    Text,
+}
+
+#[derive(Debug)]
+pub struct FormatInfo {
+    pub name: &'static str,
+    /// MIME types routed to this format; the first one is canonical
+    pub mime_types: &'static [&'static str],
+    /// Lower-case extensions without the dot
+    pub extensions: &'static [&'static str],
+    pub kind: FormatKind,
+}
+
+static SUPPORTED_FORMATS: &[FormatInfo] = &[
+    FormatInfo {
+        name: "Word document",
+        mime_types: &["application/vnd.openxmlformats-officedocument.wordprocessingml.document"],
+        extensions: &["docx"],
+        kind: FormatKind::Docx,
+    },
+    FormatInfo {
+        name: "Excel workbook",
+        mime_types: &["application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"],
+        extensions: &["xlsx"],
+        kind: FormatKind::Xlsx,
+    },
+    FormatInfo {
+        name: "PowerPoint presentation",
+        mime_types: &["application/vnd.openxmlformats-officedocument.presentationml.presentation"],
+        extensions: &["pptx"],
+        kind: FormatKind::Pptx,
+    },
+    FormatInfo {
+        name: "OpenDocument text",
+        mime_types: &["application/vnd.oasis.opendocument.text"],
+        extensions: &["odt"],
+        kind: FormatKind::Odt,
+    },
+    FormatInfo {
+        name: "OpenDocument spreadsheet",
+        mime_types: &["application/vnd.oasis.opendocument.spreadsheet"],
+        extensions: &["ods"],
+        kind: FormatKind::Ods,
+    },
+    FormatInfo {
+        name: "CSV",
+        mime_types: &["text/csv", "application/csv"],
+        extensions: &["csv"],
+        kind: FormatKind::Csv,
+    },
+    FormatInfo {
+        name: "JSON",
+        mime_types: &["application/json"],
+        extensions: &["json"],
+        kind: FormatKind::Json,
+    },
+    FormatInfo {
+        name: "XML",
+        mime_types: &["application/xml", "text/xml"],
+        extensions: &["xml"],
+        kind: FormatKind::Xml,
+    },
+    FormatInfo {
+        name: "HTML",
+        mime_types: &["text/html"],
+        extensions: &["html", "htm"],
+        kind: FormatKind::Html,
+    },
+    FormatInfo {
+        name: "Email message",
+        mime_types: &["message/rfc822"],
+        extensions: &["eml"],
+        kind: FormatKind::Eml,
+    },
+    FormatInfo {
+        name: "ZIP archive",
+        mime_types: &["application/zip"],
+        extensions: &["zip"],
+        kind: FormatKind::Zip,
+    },
+    FormatInfo {
+        name: "vCard",
+        mime_types: &["text/vcard", "text/x-vcard"],
+        extensions: &["vcf", "vcard"],
+        kind: FormatKind::Vcard,
+    },
+    FormatInfo {
+        name: "iCalendar",
+        mime_types: &["text/calendar"],
+        extensions: &["ics", "ical"],
+        kind: FormatKind::Ical,
+    },
+    FormatInfo {
+        name: "Markdown",
+        mime_types: &["text/markdown", "text/x-markdown"],
+        extensions: &["md", "markdown"],
+        kind: FormatKind::Text,
+    },
+    FormatInfo {
+        name: "Plain text",
+        mime_types: &["text/plain"],
+        extensions: &["txt"],
+        kind: FormatKind::Text,
+    },
+    FormatInfo {
+        name: "WAV audio",
+        mime_types: &["audio/wav", "audio/x-wav", "audio/wave"],
+        extensions: &["wav"],
+        kind: FormatKind::Wav,
+    },
+    FormatInfo {
+        name: "MP3 audio",
+        mime_types: &["audio/mpeg", "audio/mp3"],
+        extensions: &["mp3"],
+        kind: FormatKind::Audio,
+    },
+    FormatInfo {
+        name: "FLAC audio",
+        mime_types: &["audio/flac"],
+        extensions: &["flac"],
+        kind: FormatKind::Audio,
+    },
+    FormatInfo {
+        name: "Ogg audio",
+        mime_types: &["audio/ogg"],
+        extensions: &["ogg"],
+        kind: FormatKind::Audio,
+    },
+    FormatInfo {
+        name: "AAC audio",
+        mime_types: &["audio/aac"],
+        extensions: &["aac"],
+        kind: FormatKind::Audio,
+    },
+    FormatInfo {
+        name: "M4A audio",
+        mime_types: &["audio/x-m4a"],
+        extensions: &["m4a"],
+        kind: FormatKind::Audio,
+    },
+    FormatInfo {
+        name: "JPEG image",
+        mime_types: &["image/jpeg"],
+        extensions: &["jpg", "jpeg"],
+        kind: FormatKind::Image,
+    },
+    FormatInfo {
+        name: "PNG image",
+        mime_types: &["image/png"],
+        extensions: &["png"],
+        kind: FormatKind::Image,
+    },
+    FormatInfo {
+        name: "GIF image",
+        mime_types: &["image/gif"],
+        extensions: &["gif"],
+        kind: FormatKind::Image,
+    },
+    FormatInfo {
+        name: "SVG image",
+        mime_types: &["image/svg+xml"],
+        extensions: &["svg"],
+        kind: FormatKind::Image,
+    },
+];
+
+/// All formats `convert` accepts
+pub fn supported_formats() -> &'static [FormatInfo] {
+    SUPPORTED_FORMATS
+}
+
+/// Canonical MIME type for a (case-insensitive) file extension
+pub fn mime_for_extension(extension: &str) -> Option<&'static str> {
+    let extension = extension.to_lowercase();
+    SUPPORTED_FORMATS
+        .iter()
+        .find(|format| format.extensions.contains(&extension.as_str()))
+        .map(|format| format.mime_types[0])
+}
+
+pub fn format_for_mime(mime_type: &str) -> Option<&'static FormatInfo> {
+    SUPPORTED_FORMATS
+        .iter()
+        .find(|format| format.mime_types.contains(&mime_type))
+}
@@ -1,49 +1,109 @@
 use csv::ReaderBuilder;
 use std::io::Cursor;
+use crate::config::SETTINGS;
+use crate::markdown::MarkdownBuilder;
+use crate::{output_limit, truncate_output};
+
+// Delimiters seen in the wild: Excel uses `;` in locales where `,` is the decimal mark
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
 
 pub fn run(bytes: &[u8]) -> Result<String, String> {
+    run_with_delimiter(bytes, detect_delimiter(bytes))
+}
+
+// The candidate that appears (outside quotes) on every one of the first lines,
+// most often; `,` when nothing stands out
+fn detect_delimiter(bytes: &[u8]) -> u8 {
+    let sample = String::from_utf8_lossy(&bytes[..bytes.len().min(8192)]);
+    let lines: Vec<&str> = sample.lines().filter(|line| !line.trim().is_empty()).take(10).collect();
+
+    DELIMITER_CANDIDATES
+        .iter()
+        .map(|&candidate| {
+            let min_count = lines
+                .iter()
+                .map(|line| count_unquoted(line, candidate))
+                .min()
+                .unwrap_or(0);
+            (candidate, min_count)
+        })
+        .filter(|&(_, count)| count > 0)
+        .max_by_key(|&(candidate, count)| (count, candidate == b','))
+        .map(|(candidate, _)| candidate)
+        .unwrap_or(b',')
+}
+
+fn count_unquoted(line: &str, delimiter: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for byte in line.bytes() {
+        if byte == b'"' {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+// "1.234,56" -> "1234.56", "-0,5" -> "-0.5"; anything else is returned unchanged
+fn normalize_decimal(cell: &str) -> String {
+    let digits = cell.strip_prefix('-').unwrap_or(cell);
+    let Some((integer, fraction)) = digits.split_once(',') else {
+        return cell.to_string();
+    };
+    let groups: Vec<&str> = integer.split('.').collect();
+    let integer_ok = !groups[0].is_empty()
+        && groups.iter().all(|group| group.bytes().all(|b| b.is_ascii_digit()))
+        && (groups.len() == 1 || (groups[0].len() <= 3 && groups[1..].iter().all(|group| group.len() == 3)));
+    let fraction_ok = !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit());
+    if !(integer_ok && fraction_ok) {
+        return cell.to_string();
+    }
+
+    let sign = if cell.starts_with('-') { "-" } else { "" };
+    format!("{}{}.{}", sign, groups.concat(), fraction)
+}
+
+pub fn run_with_delimiter(bytes: &[u8], delimiter: u8) -> Result<String, String> {
+    // Excel's "CSV UTF-8" export starts with a BOM that would stick to the first header
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
     let cursor = Cursor::new(bytes);
     let mut rdr = ReaderBuilder::new()
         .has_headers(true)
+        .delimiter(delimiter)
         .from_reader(cursor);
     
-    let mut markdown = String::new();
+    let limit = output_limit();
+    let normalize_decimals = SETTINGS.read().unwrap().normalize_decimals;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    // Stop collecting once the rows alone would exceed the output limit
+    let mut collected_bytes = 0;
     
     // Extract headers before iterating over records
     if let Ok(headers) = rdr.headers() {
-        let header_row = headers
-            .iter()
-            .map(|h| h.trim())
-            .collect::<Vec<&str>>()
-            .join(" | ");
-        markdown.push_str("| ");
-        markdown.push_str(&header_row);
-        markdown.push_str(" |\n");
-        
-        // Add separator row
-        let separator = headers
-            .iter()
-            .map(|_| "---")
-            .collect::<Vec<&str>>()
-            .join(" | ");
-        markdown.push_str("| ");
-        markdown.push_str(&separator);
-        markdown.push_str(" |\n");
+        rows.push(headers.iter().map(|h| h.trim().to_string()).collect());
     }
     
     for result in rdr.records() {
         match result {
             Ok(record) => {
-                
-                // Write data row
-                let row = record
+                let row: Vec<String> = record
                     .iter()
-                    .map(|cell| cell.trim())
-                    .collect::<Vec<&str>>()
-                    .join(" | ");
-                markdown.push_str("| ");
-                markdown.push_str(&row);
-                markdown.push_str(" |\n");
+                    .map(|cell| {
+                        if normalize_decimals {
+                            normalize_decimal(cell.trim())
+                        } else {
+                            cell.trim().to_string()
+                        }
+                    })
+                    .collect();
+                collected_bytes += row.iter().map(|cell| cell.len() + 3).sum::<usize>();
+                rows.push(row);
+
+                if limit.is_some_and(|limit| collected_bytes > limit) {
+                    break;
+                }
             }
             Err(err) => {
                 return Err(format!("CSV parsing error: {}", err));
@@ -51,9 +111,43 @@ pub fn run(bytes: &[u8]) -> Result<String, String> {
         }
     }
     
+    let mut md = MarkdownBuilder::new();
+    if let Some((headers, records)) = rows.split_first() {
+        md.table(headers, records);
+    }
+    let mut markdown = md.finish();
     if markdown.is_empty() {
         return Err("Empty or invalid CSV data".to_string());
     }
+    if let Some(limit) = limit {
+        truncate_output(&mut markdown, limit);
+    }
     
     Ok(markdown)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bom_is_stripped() {
+        let markdown = run(b"\xEF\xBB\xBFName,Age\nAda,36\n").unwrap();
+        assert!(markdown.starts_with("| Name | Age |\n"));
+    }
+
+    #[test]
+    fn test_semicolon_delimiter_is_detected() {
+        assert_eq!(detect_delimiter(b"Name;Price\nTea;1,50\n\"A;B\";2\n"), b';');
+        assert_eq!(detect_delimiter(b"a,b\n1,2\n"), b',');
+        assert_eq!(detect_delimiter(b"single column\n"), b',');
+    }
+
+    #[test]
+    fn test_normalize_decimal() {
+        assert_eq!(normalize_decimal("1.234,56"), "1234.56");
+        assert_eq!(normalize_decimal("-0,5"), "-0.5");
+        assert_eq!(normalize_decimal("12.34,5"), "12.34,5");
+        assert_eq!(normalize_decimal("Tea, green"), "Tea, green");
+    }
+}
@@ -1,21 +1,85 @@
-use std::io::{Cursor, Read};
+use std::io::Cursor;
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::path::Path;
 use zip::ZipArchive;
 use docx_rust::{
-    document::{BodyContent, TableCellContent, TableRowContent, ParagraphContent},
+    document::{BodyContent, BreakType, TableCellContent, TableRowContent, ParagraphContent, Run, RunContent},
+    formatting::{JustificationVal, VMergeType},
     DocxFile,
 };
+use crate::converter::omml2latex;
 use crate::generator::image2md::{self, ImageProcessingMode};
-use crate::config::SETTINGS;
+use crate::config::{Settings, SETTINGS};
+use crate::markdown::MarkdownFlavor;
+use crate::opc;
+use crate::table::{render_aligned_table, Alignment, TableStyle};
+use quick_xml::events::Event;
+use once_cell::sync::Lazy;
+use quick_xml::Reader;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
 
+/// What happens to tracked changes (`w:ins`/`w:del`) (`Settings.track_changes`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackChangesMode {
+    /// Keep inserted text, drop deleted text: the document as it would read after accepting
+    AcceptAll,
+    /// Drop inserted text, keep deleted text: the document before the edits
+    RejectAll,
+    /// Keep both, inserted text as `<ins>...</ins>` and deleted text as `~~...~~`
+    /// (`<del>...</del>` for the CommonMark flavor); pandoc's change spans are
+    /// rewritten into the same markup
+    ShowBoth,
+}
+
+/// How right-to-left paragraphs (`w:bidi`, or runs that are all `w:rtl`) keep their
+/// direction (`Settings.rtl_markup`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RtlMarkup {
+    /// Start the text with a RIGHT-TO-LEFT MARK, which renderers that pick the direction
+    /// from the first strong character (GitHub, most editors) follow
+    Mark,
+    /// Wrap the paragraph in `<div dir="rtl">`, for renderers that default to left-to-right
+    Html,
+    /// Leave the text as it is
+    Off,
+}
+
+/// DOCX -> Markdown with pandoc when it is installed and can honour the settings
+/// (see `pandoc_matches_settings`), otherwise with the built-in reader
 pub fn run(file_stream: &[u8]) -> Result<String, String> {
-    // Check if pandoc is available
-    if is_pandoc_available() {
-        run_with_pandoc(file_stream)
+    let mut markdown = if is_pandoc_available() && pandoc_matches_settings(file_stream) {
+        run_with_pandoc(file_stream)?
     } else {
-        run_with_images(file_stream)
+        run_with_images(file_stream)?
+    };
+    super::append_attachments(&mut markdown, file_stream, "word/embeddings/");
+    Ok(markdown)
+}
+
+/// Word count Word recorded in `docProps/app.xml`, if the document has one
+pub fn app_word_count(file_stream: &[u8]) -> Option<usize> {
+    let mut archive = ZipArchive::new(Cursor::new(file_stream)).ok()?;
+    let app_xml = opc::read_part_to_string(&mut archive, "docProps/app.xml").ok()??;
+
+    let mut reader = Reader::from_str(&app_xml);
+    let mut in_words = false;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => in_words = element.name().as_ref() == b"Words",
+            Ok(Event::Text(text)) if in_words => {
+                return text.unescape().ok()?.trim().parse().ok();
+            }
+            Ok(Event::End(_)) => in_words = false,
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+        buf.clear();
     }
 }
 
@@ -26,13 +90,45 @@ fn is_pandoc_available() -> bool {
         .is_ok()
 }
 
+// pandoc follows the flavor, track changes, the images settings and the document
+// title, but writes bullets, tables and empty cells its own way and doesn't mark
+// right-to-left text; for anything else than those defaults the built-in reader
+// runs instead, so the output doesn't depend on whether pandoc is on PATH
+fn pandoc_matches_settings(file_stream: &[u8]) -> bool {
+    let cfg = SETTINGS.read().unwrap();
+    let mismatch = if cfg.bullet_char != '-' {
+        Some("bullet_char")
+    } else if cfg.table_style != TableStyle::Markdown {
+        Some("table_style")
+    } else if !cfg.empty_cell_placeholder.is_empty() {
+        Some("empty_cell_placeholder")
+    } else if cfg.rtl_markup != RtlMarkup::Off && has_rtl_paragraphs(file_stream) {
+        Some("rtl_markup")
+    } else {
+        None
+    };
+    if let Some(setting) = mismatch {
+        log::debug!("pandoc skipped: it can't follow {}", setting);
+    }
+    mismatch.is_none()
+}
+
+fn has_rtl_paragraphs(file_stream: &[u8]) -> bool {
+    ZipArchive::new(Cursor::new(file_stream))
+        .map(|mut archive| body_paragraphs(&mut archive).iter().any(|paragraph| paragraph.rtl))
+        .unwrap_or(false)
+}
+
 fn run_with_pandoc(file_stream: &[u8]) -> Result<String, String> {
     let cfg = &*SETTINGS.read().unwrap();
 
-    // Create a temporary file for the DOCX input
-    let temp_dir = std::env::temp_dir();
-    let input_path = temp_dir.join("temp_input.docx");
-    let output_path = temp_dir.join("temp_output.md");
+    // A private directory per call for pandoc's input and output; removed on drop
+    let work_dir = tempfile::Builder::new()
+        .prefix("markitup-docx-")
+        .tempdir_in(cfg.resolved_temp_dir())
+        .map_err(|e| format!("Failed to create temporary directory: {}", e))?;
+    let input_path = work_dir.path().join("input.docx");
+    let output_path = work_dir.path().join("output.md");
     
     // Write DOCX data to temporary file
     std::fs::write(&input_path, file_stream)
@@ -46,13 +142,23 @@ fn run_with_pandoc(file_stream: &[u8]) -> Result<String, String> {
         .arg("-f")
         .arg("docx")
         .arg("-t")
-        .arg("markdown");
+        .arg(match cfg.flavor {
+            MarkdownFlavor::Gfm => "gfm",
+            MarkdownFlavor::CommonMark => "commonmark",
+        })
+        .arg(match cfg.track_changes {
+            TrackChangesMode::AcceptAll => "--track-changes=accept",
+            TrackChangesMode::RejectAll => "--track-changes=reject",
+            TrackChangesMode::ShowBoth => "--track-changes=all",
+        });
     
-    // Handle image extraction based on configuration
-    if !cfg.image_path.as_os_str().is_empty() {
-        // Extract images to configured directory
+    // SaveToFile: pandoc extracts the pictures into the work directory; they are
+    // handed back with the result below, like the built-in reader's
+    let save_images = !cfg.skip_images && cfg.image_processing_mode() == ImageProcessingMode::SaveToFile;
+    let media_dir = work_dir.path().join("media");
+    if save_images {
         cmd.arg("--extract-media")
-            .arg(&cfg.image_path);
+            .arg(&media_dir);
     }
     
     // Execute pandoc
@@ -68,50 +174,113 @@ fn run_with_pandoc(file_stream: &[u8]) -> Result<String, String> {
     let mut markdown = std::fs::read_to_string(&output_path)
         .map_err(|e| format!("Failed to read pandoc output: {}", e))?;
     
-    // Clean up temporary files
-    let _ = std::fs::remove_file(&input_path);
-    let _ = std::fs::remove_file(&output_path);
-    
     // Post-process images if needed
-    if !cfg.image_path.as_os_str().is_empty() {
-        markdown = process_pandoc_images(markdown)?;
+    if cfg.skip_images {
+        markdown = remove_image_links(&markdown);
+    } else if save_images {
+        markdown = defer_pandoc_media(markdown, &media_dir, cfg)?;
     } else {
         // Convert image references to base64 if no image_path is configured
         markdown = convert_image_refs_to_base64(markdown)?;
     }
     
-    Ok(markdown)
+    if cfg.track_changes == TrackChangesMode::ShowBoth {
+        markdown = mark_pandoc_changes(&markdown, cfg.flavor);
+    }
+    // pandoc writes checkbox controls as their ☐/☒ glyph
+    let markdown = glyph_task_items(&markdown, cfg.bullet_char, cfg.flavor);
+    Ok(super::title_heading(file_stream) + &markdown)
 }
 
-fn process_pandoc_images(markdown: String) -> Result<String, String> {
-    let cfg = &*SETTINGS.read().unwrap();
-    
-    // If we have an output path, calculate relative paths
-    if let Some(output_path) = &cfg.output_path {
-        if !output_path.as_os_str().is_empty() {
-            // Calculate relative path from output file's directory to image directory
-            let output_dir = output_path.parent().unwrap_or(Path::new("."));
-            
-            // Pandoc creates a 'media' subdirectory under the specified extract-media path
-            let pandoc_media_path = cfg.image_path.join("media");
-            
-            if let Ok(relative_path) = pandoc_media_path.strip_prefix(output_dir) {
-                let relative_str = relative_path.to_string_lossy();
-                
-                // Replace pandoc's absolute media paths with relative paths  
-                let updated = markdown.replace(
-                    &format!("]({})", pandoc_media_path.to_string_lossy()),
-                    &format!("](./{})", relative_str)
-                );
-                return Ok(updated);
+// `--track-changes=all` keeps each edit as `<span class="insertion" author=.. date=..>`
+// (`deletion`), plus empty spans for inserted or deleted paragraph marks
+static CHANGE_SPAN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)<span class="(insertion|deletion|paragraph-insertion|paragraph-deletion)"[^>]*>(.*?)</span>"#).unwrap()
+});
+
+// The change spans in the markup the built-in reader uses for `ShowBoth`
+fn mark_pandoc_changes(markdown: &str, flavor: MarkdownFlavor) -> String {
+    CHANGE_SPAN
+        .replace_all(markdown, |caps: &Captures| {
+            let (open, close) = match &caps[1] {
+                "insertion" => ("<ins>", "</ins>"),
+                "deletion" => flavor.strikethrough(),
+                _ => ("", ""),
+            };
+            mark_change(&caps[2], open, close)
+        })
+        .into_owned()
+}
+
+// Lines that start with a checkbox glyph as task-list items
+fn glyph_task_items(markdown: &str, bullet: char, flavor: MarkdownFlavor) -> String {
+    let mut out: String = markdown
+        .lines()
+        .map(|line| match line.trim_start().starts_with(CHECKBOX_GLYPHS) {
+            true => task_item(line, !line.trim_start().starts_with('\u{2610}'), bullet, flavor),
+            false => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if markdown.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+// Name the pictures pandoc extracted under `media_dir` like any other saved image,
+// queue them for `Conversion::images` and point their links at the new names
+fn defer_pandoc_media(mut markdown: String, media_dir: &Path, cfg: &Settings) -> Result<String, String> {
+    let mut files = Vec::new();
+    collect_files(media_dir, &mut files);
+    // Pandoc links each file by its full path; number the pictures in reading order
+    files.sort_by_key(|path| markdown.find(&*path.to_string_lossy()).unwrap_or(usize::MAX));
+
+    for path in files {
+        let data = fs::read(&path).map_err(|e| format!("Failed to read extracted image: {}", e))?;
+        let extension = path
+            .extension()
+            .map(|ext| image2md::sanitize_file_name(&ext.to_string_lossy().to_lowercase()))
+            .unwrap_or_else(|| "png".to_string());
+        let filename = format!("{}.{}", image2md::saved_image_name(cfg, &data), extension);
+
+        // Outside a conversion the picture is written right away
+        if !crate::context::defer_image(PathBuf::from(&filename), &data) {
+            let file_path = cfg.image_path.join(&filename);
+            if let Some(parent) = file_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create image directory: {}", e))?;
             }
+            fs::write(&file_path, &data).map_err(|e| format!("Failed to save image file: {}", e))?;
         }
+        markdown = markdown.replace(&*path.to_string_lossy(), &image2md::link_target(cfg, &filename));
     }
-    
-    // If no output path or empty output path, use absolute paths
     Ok(markdown)
 }
 
+// Every file below `dir`; an unreadable directory adds nothing
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+// `![alt](target)`, or an `<img>` tag for a picture with a size
+static IMAGE_LINK: Lazy<Regex> = Lazy::new(|| Regex::new(r"!\[[^\]]*\]\([^)]*\)|<img\b[^>]*>").unwrap());
+
+// skip_images: pandoc has no switch to leave pictures out, so their links are removed
+fn remove_image_links(markdown: &str) -> String {
+    IMAGE_LINK.replace_all(markdown, "").into_owned()
+}
+
 fn convert_image_refs_to_base64(markdown: String) -> Result<String, String> {
     // This is a simplified approach - in practice, you'd need to parse the markdown
     // and find image references, read the files, and convert them to base64
@@ -125,22 +294,10 @@ fn run_with_images(file_stream: &[u8]) -> Result<String, String> {
     let mut archive = ZipArchive::new(cursor)
         .map_err(|e| format!("Failed to open DOCX archive: {}", e))?;
 
-    // First, extract all images from the archive
-    let mut images = HashMap::new();
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| format!("Failed to access file in ZIP archive: {}", e))?;
-        
-        if file.name().starts_with("word/media/") {
-            let mut image_data = Vec::new();
-            file.read_to_end(&mut image_data)
-                .map_err(|e| format!("Failed to read image data: {}", e))?;
-            
-            let filename = file.name().to_string();
-            images.insert(filename, image_data);
-        }
-    }
+    // Read once; paragraphs and table cells get a reference
+    let settings = SETTINGS.read().unwrap().clone();
+    let images = DocumentImages::read(&mut archive, &settings)?;
+    let body_paragraphs = body_paragraphs(&mut archive);
 
     // Reset cursor and parse DOCX with docx_rust
     let cursor = Cursor::new(file_stream);
@@ -150,20 +307,36 @@ fn run_with_images(file_stream: &[u8]) -> Result<String, String> {
     let doc = docx_file.parse()
         .map_err(|e| format!("Failed to parse DOCX file: {}", e))?;
 
-    let mut markdown = String::new();
-    markdown.push_str("# Document\n\n");
+    let mut markdown = super::title_heading(file_stream);
+    let mut paragraph_index = 0;
+    let mut previous_task = false;
 
     for content in doc.document.body.content {
         match content {
             BodyContent::Paragraph(paragraph) => {
-                let paragraph_md = process_paragraph(&paragraph, &images)?;
+                let info = body_paragraphs.get(paragraph_index).cloned().unwrap_or_default();
+                let mut paragraph_md = process_paragraph(&paragraph, &images, &settings, &info.equations)?;
+                let checkbox = info.checkbox;
+                if let Some(checked) = checkbox {
+                    paragraph_md = task_item(&paragraph_md, checked, settings.bullet_char, settings.flavor);
+                }
+                if info.rtl && !paragraph_md.trim().is_empty() {
+                    paragraph_md = mark_rtl(&paragraph_md, settings.rtl_markup);
+                }
+                paragraph_index += 1;
+                // Checkbox paragraphs in a row make up one task list
+                if checkbox.is_some() && previous_task {
+                    markdown.pop();
+                }
+                previous_task = checkbox.is_some();
                 if !paragraph_md.trim().is_empty() {
                     markdown.push_str(&paragraph_md);
                     markdown.push_str("\n\n");
                 }
             }
             BodyContent::Table(table) => {
-                let table_md = process_table(&table)?;
+                previous_task = false;
+                let table_md = process_table(&table, &settings)?;
                 if !table_md.trim().is_empty() {
                     markdown.push_str(&table_md);
                     markdown.push_str("\n\n");
@@ -176,9 +349,199 @@ fn run_with_images(file_stream: &[u8]) -> Result<String, String> {
     Ok(markdown)
 }
 
+// What docx_rust doesn't expose about a body-level paragraph
+#[derive(Debug, Clone, Default, PartialEq)]
+struct BodyParagraph {
+    // Some(checked) when it holds a checkbox content control (`w14:checkbox`)
+    checkbox: Option<bool>,
+    // right-to-left: `w:bidi` in its properties, or every text run marked `w:rtl`
+    rtl: bool,
+    // its equations (`m:oMath`), which docx_rust doesn't read, in order
+    equations: Vec<Equation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Equation {
+    // runs of the paragraph before it: `w:r` children of the `w:p` and of its `w:ins`/`w:del`,
+    // the runs `process_paragraph` counts
+    run_index: usize,
+    // None: the equation uses OMML that `omml2latex` doesn't cover
+    latex: Option<String>,
+    display: bool,
+}
+
+impl Equation {
+    // `$...$` inline, a `$$` block for display equations (`m:oMathPara`)
+    fn markdown(&self) -> String {
+        match (&self.latex, self.display) {
+            (Some(latex), false) => format!("${}$", latex),
+            (Some(latex), true) => format!("\n\n$$\n{}\n$$\n\n", latex),
+            (None, _) => EQUATION_PLACEHOLDER.to_string(),
+        }
+    }
+}
+
+// Stands in for an equation that could not be converted
+const EQUATION_PLACEHOLDER: &str = "[equation]";
+
+// Per body-level `w:p` of word/document.xml, in order. The part is scanned directly;
+// an unreadable part just means no checkboxes, RTL paragraphs or equations.
+fn body_paragraphs(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Vec<BodyParagraph> {
+    let Ok(Some(xml)) = opc::read_part_to_string(archive, "word/document.xml") else {
+        return Vec::new();
+    };
+    scan_body_paragraphs(&xml)
+}
+
+fn scan_body_paragraphs(xml: &str) -> Vec<BodyParagraph> {
+    let mut reader = Reader::from_str(xml);
+    // w:document > w:body > w:p > ...
+    let mut path: Vec<Vec<u8>> = Vec::new();
+    let mut paragraphs: Vec<BodyParagraph> = Vec::new();
+    // per paragraph: runs with text, and how many of them are `w:rtl`
+    let (mut text_runs, mut rtl_runs) = (0, 0);
+    let (mut run_rtl, mut run_counted) = (false, false);
+    // runs so far in the paragraph, as `Equation::run_index` counts them
+    let mut runs_seen = 0;
+
+    loop {
+        let in_body_paragraph = path.len() > 2 && path[1] == b"w:body" && path[2] == b"w:p";
+        let (element, is_start) = match reader.read_event() {
+            Ok(Event::Start(element)) => (element, true),
+            Ok(Event::Empty(element)) => (element, false),
+            Ok(Event::End(_)) => {
+                path.pop();
+                continue;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => continue,
+        };
+        let name = element.name().as_ref().to_vec();
+        if name == b"w:p" && path.len() == 2 && path[1] == b"w:body" {
+            finish_paragraph(paragraphs.last_mut(), text_runs, rtl_runs);
+            (text_runs, rtl_runs, runs_seen) = (0, 0, 0);
+            paragraphs.push(BodyParagraph::default());
+        } else if in_body_paragraph && name == b"m:oMath" && is_start {
+            // The whole equation is read here, so it never enters `path`
+            let node = omml2latex::read_math(&mut reader, &element);
+            let latex = omml2latex::to_latex(&node)
+                .map_err(|unsupported| {
+                    crate::context::warn(format!("Equation with {} kept as {}", unsupported, EQUATION_PLACEHOLDER));
+                })
+                .ok();
+            let display = path.iter().any(|name| name == b"m:oMathPara");
+            if latex.as_ref().is_none_or(|latex| !latex.is_empty()) {
+                let equation = Equation { run_index: runs_seen, latex, display };
+                paragraphs.last_mut().unwrap().equations.push(equation);
+            }
+            continue;
+        } else if in_body_paragraph {
+            let state = paragraphs.last_mut().unwrap();
+            let parent = path.last().map(Vec::as_slice);
+            match name.as_slice() {
+                b"w14:checkbox" => state.checkbox = state.checkbox.or(Some(false)),
+                b"w14:checked" => {
+                    let value = opc::attribute_value(&element, b"w14:val").ok().flatten();
+                    state.checkbox = Some(matches!(value.as_deref(), Some("1" | "true")));
+                }
+                b"w:bidi" if path.len() == 4 && parent == Some(b"w:pPr") => state.rtl = is_on(&element),
+                b"w:r" => {
+                    (run_rtl, run_counted) = (false, false);
+                    let counted = path.len() == 3
+                        || (path.len() == 4 && matches!(path[3].as_slice(), b"w:ins" | b"w:del"));
+                    runs_seen += usize::from(counted);
+                }
+                b"w:rtl" if parent == Some(b"w:rPr") => run_rtl = is_on(&element),
+                b"w:t" if !run_counted => {
+                    text_runs += 1;
+                    rtl_runs += usize::from(run_rtl);
+                    run_counted = true;
+                }
+                _ => {}
+            }
+        }
+        if is_start {
+            path.push(name);
+        }
+    }
+    finish_paragraph(paragraphs.last_mut(), text_runs, rtl_runs);
+    paragraphs
+}
+
+// A paragraph without `w:bidi` is still RTL when all of its text is in RTL runs
+fn finish_paragraph(paragraph: Option<&mut BodyParagraph>, text_runs: usize, rtl_runs: usize) {
+    if let Some(paragraph) = paragraph {
+        paragraph.rtl |= text_runs > 0 && rtl_runs == text_runs;
+    }
+}
+
+// Put the equations in for the `EQUATION` marks `process_paragraph` left, in order
+fn place_equations(paragraph_md: &str, equations: &[Equation]) -> String {
+    if equations.is_empty() {
+        return paragraph_md.to_string();
+    }
+    let mut parts = paragraph_md.split(EQUATION);
+    let mut placed = parts.next().unwrap_or_default().to_string();
+    for (part, equation) in parts.zip(equations) {
+        placed.push_str(&equation.markdown());
+        placed.push_str(part);
+    }
+    placed.trim().to_string()
+}
+
+// On/off properties: present means on unless `w:val` says "0"/"false"
+fn is_on(element: &quick_xml::events::BytesStart) -> bool {
+    let value = opc::attribute_value(element, b"w:val").ok().flatten();
+    !matches!(value.as_deref(), Some("0" | "false" | "off"))
+}
+
+const RIGHT_TO_LEFT_MARK: char = '\u{200F}';
+
+// Keep an RTL paragraph right-to-left; the mark goes after any heading/list marker
+// so the Markdown syntax still parses
+fn mark_rtl(paragraph_md: &str, markup: RtlMarkup) -> String {
+    match markup {
+        RtlMarkup::Off => paragraph_md.to_string(),
+        RtlMarkup::Html => format!("<div dir=\"rtl\">\n\n{}\n\n</div>", paragraph_md.trim()),
+        RtlMarkup::Mark => {
+            let text = paragraph_md.trim_start();
+            let marker_len = text
+                .split_once(' ')
+                .filter(|(marker, _)| is_block_marker(marker))
+                .map_or(0, |(marker, _)| marker.len() + 1);
+            // `- [x] ` task items: the checkbox belongs to the marker too
+            let marker_len = match text[marker_len..].split_once(' ') {
+                Some(("[x]" | "[ ]", _)) if marker_len > 0 => marker_len + 4,
+                _ => marker_len,
+            };
+            format!("{}{}{}", &text[..marker_len], RIGHT_TO_LEFT_MARK, &text[marker_len..])
+        }
+    }
+}
+
+// `#`..`######` or a bullet
+fn is_block_marker(marker: &str) -> bool {
+    (!marker.is_empty() && marker.len() <= 6 && marker.chars().all(|c| c == '#'))
+        || matches!(marker, "-" | "*" | "+")
+}
+
+// ☐ ☑ ☒, as Word draws checkbox content controls
+const CHECKBOX_GLYPHS: [char; 3] = ['\u{2610}', '\u{2611}', '\u{2612}'];
+
+// A checkbox paragraph as a list item; the control's own ☐/☒ glyph is dropped
+fn task_item(paragraph_md: &str, checked: bool, bullet: char, flavor: MarkdownFlavor) -> String {
+    let text = paragraph_md
+        .trim()
+        .trim_start_matches(CHECKBOX_GLYPHS)
+        .trim_start();
+    format!("{} {} {}", bullet, flavor.task_marker(checked), text).trim_end().to_string()
+}
+
 fn process_paragraph(
     paragraph: &docx_rust::document::Paragraph,
-    images: &HashMap<String, Vec<u8>>
+    images: &DocumentImages,
+    settings: &Settings,
+    equations: &[Equation],
 ) -> Result<String, String> {
     let mut text_content = String::new();
     let mut is_heading = false;
@@ -198,111 +561,199 @@ fn process_paragraph(
     let mut has_bold = false;
     let mut font_size: Option<f32> = None;
 
+    // Equations go in between the runs, as `EQUATION` marks until the text is final
+    let mut run_index = 0;
+    let mut pending = equations.iter().peekable();
+    let mut mark_equations = |text: &mut String, run_index: usize| {
+        while pending.next_if(|equation| equation.run_index <= run_index).is_some() {
+            text.push(EQUATION);
+        }
+    };
+
     for content in &paragraph.content {
-        match content {
-            ParagraphContent::Run(run) => {
-                // Check run properties for formatting
-                if let Some(props) = &run.property {
-                    if props.bold.is_some() {
-                        has_bold = true;
-                    }
-                    if let Some(size) = &props.size {
-                        font_size = Some(size.value as f32 / 2.0); // Convert half-points to points
-                    }
+        // An equation before a tracked change stays outside its markup
+        mark_equations(&mut text_content, run_index);
+        let all_runs = match content {
+            ParagraphContent::Run(run) => std::slice::from_ref(run),
+            ParagraphContent::Insertion(insertion) => insertion.runs.as_slice(),
+            ParagraphContent::Deletion(deletion) => deletion.runs.as_slice(),
+            _ => &[],
+        };
+        let Some((runs, open, close)) = tracked_runs(content, settings.track_changes, settings.flavor) else {
+            run_index += all_runs.len();
+            continue;
+        };
+
+        let mut segment = String::new();
+        for run in runs {
+            mark_equations(&mut segment, run_index);
+            run_index += 1;
+            // Check run properties for formatting
+            if let Some(props) = &run.property {
+                if props.bold.is_some() {
+                    has_bold = true;
                 }
+                if let Some(size) = &props.size {
+                    font_size = Some(size.value as f32 / 2.0); // Convert half-points to points
+                }
+            }
 
-                // Extract text from run
-                for run_content in &run.content {
-                    match run_content {
-                        docx_rust::document::RunContent::Text(text) => {
-                            text_content.push_str(&text.text);
-                        }
-                        docx_rust::document::RunContent::Drawing(_drawing) => {
-                            // Process embedded images in drawings with proper mode
-                            if let Some(image_md) = process_drawing_images_with_mode(images)? {
-                                text_content.push_str(&image_md);
-                            }
+            // Extract text from run
+            for run_content in &run.content {
+                match run_content {
+                    RunContent::Text(text) => segment.push_str(&text.text),
+                    RunContent::DelText(text) => segment.push_str(&text.text),
+                    // `w:br`: page breaks end the paragraph, the others (line, column) become hard line breaks
+                    RunContent::Break(br) => match br.ty {
+                        Some(BreakType::Page) => segment.push(PAGE_BREAK),
+                        _ => segment.push('\n'),
+                    },
+                    RunContent::Drawing(drawing) => {
+                        // Process embedded images in drawings with proper mode
+                        if let Some(image_md) = process_drawing_images_with_mode(drawing, images)? {
+                            segment.push_str(&image_md);
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
             }
-            _ => {}
         }
+        text_content.push_str(&mark_change(&segment, open, close));
     }
+    mark_equations(&mut text_content, usize::MAX);
 
     // Determine final heading status
+    let text = text_content.replace([PAGE_BREAK, EQUATION], "");
     let (final_is_heading, final_level) = determine_heading_status(
         is_heading,
         heading_level,
         has_bold,
         font_size,
-        &text_content
+        &text
     );
 
-    if final_is_heading && !text_content.trim().is_empty() {
-        let heading_prefix = "#".repeat(final_level.min(6));
-        Ok(format!("{} {}", heading_prefix, text_content.trim()))
-    } else {
-        Ok(text_content)
+    let heading = (final_is_heading && !text.trim().is_empty()).then_some(final_level);
+    if heading.is_some() && !is_heading {
+        crate::context::warn(format!(
+            "Heading inferred from formatting: {:?}",
+            text.trim()
+        ));
+    }
+    if !text_content.contains(PAGE_BREAK) {
+        return Ok(place_equations(&paragraph_part(&text_content, heading), equations));
     }
+
+    // Text on either side of a page break becomes its own block
+    let mut blocks = Vec::new();
+    for (index, part) in text_content.split(PAGE_BREAK).enumerate() {
+        if index > 0 {
+            blocks.push(PAGE_BREAK_MARKER.to_string());
+        }
+        let part = paragraph_part(part, heading);
+        if !part.trim().is_empty() {
+            blocks.push(part.trim().to_string());
+        }
+    }
+    Ok(place_equations(&blocks.join("\n\n"), equations))
 }
 
-fn process_drawing_images_with_mode(images: &HashMap<String, Vec<u8>>) -> Result<Option<String>, String> {
-    let cfg = &*SETTINGS.read().unwrap();
-    
-    // Determine processing mode based on configuration
-    let mode = if cfg.image_path.as_os_str().is_empty() {
-        ImageProcessingMode::Base64
-    } else {
-        ImageProcessingMode::SaveToFile
-    };
-    
-    // Process the first available image (simplified approach)
-    for (filename, image_data) in images {
-        if filename.ends_with(".png") || 
-           filename.ends_with(".jpg") || 
-           filename.ends_with(".jpeg") ||
-           filename.ends_with(".gif") ||
-           filename.ends_with(".webp") {
-            
-            let image_md = image2md::run_with_mode(image_data, mode)?;
-            
-            // Handle relative paths if needed
-            let final_md = if !cfg.image_path.as_os_str().is_empty() {
-                adjust_image_path_in_markdown(image_md)?
-            } else {
-                image_md
-            };
-            
-            return Ok(Some(format!("\n\n{}\n\n", final_md)));
+// Stands for `w:br w:type="page"` while a paragraph is collected
+const PAGE_BREAK: char = '\u{c}';
+// Stands for an equation (`BodyParagraph::equations`) while a paragraph is collected
+const EQUATION: char = '\u{e}';
+const PAGE_BREAK_MARKER: &str = "<!-- pagebreak -->";
+
+// A heading's lines are joined (headings are one line); elsewhere each `w:br` is a hard break
+fn paragraph_part(text: &str, heading: Option<usize>) -> String {
+    match heading {
+        Some(level) if !text.trim().is_empty() => {
+            let lines: Vec<&str> = text.split('\n').map(str::trim).filter(|line| !line.is_empty()).collect();
+            format!("{} {}", "#".repeat(level.min(6)), lines.join(" "))
         }
+        _ if text.contains('\n') => text
+            .trim_matches('\n')
+            .split('\n')
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\\\n"),
+        _ => text.to_string(),
     }
-    Ok(None)
 }
 
-fn adjust_image_path_in_markdown(markdown: String) -> Result<String, String> {
-    let cfg = &*SETTINGS.read().unwrap();
-    
-    // If we have an output path, try to make image paths relative
-    if let Some(output_path) = &cfg.output_path {
-        if !output_path.as_os_str().is_empty() {
-            // Calculate relative path from output file's directory to image directory
-            let output_dir = output_path.parent().unwrap_or(Path::new("."));
-            
-            if let Ok(relative_path) = cfg.image_path.strip_prefix(output_dir) {
-                // Replace absolute image paths with relative ones
-                let relative_str = relative_path.to_string_lossy();
-                return Ok(markdown.replace(
-                    &format!("]({})", cfg.image_path.to_string_lossy()),
-                    &format!("](./{})", relative_str)
-                ));
-            }
+// The document's pictures, found through their relationship id (`r:embed`), and the
+// settings they are rendered with; read once per document
+struct DocumentImages {
+    // relationship id -> media part
+    parts: HashMap<String, String>,
+    // media part -> bytes
+    media: HashMap<String, Vec<u8>>,
+    mode: ImageProcessingMode,
+    skip: bool,
+}
+
+impl DocumentImages {
+    // Nothing is read when images are skipped
+    fn read(archive: &mut ZipArchive<Cursor<&[u8]>>, settings: &Settings) -> Result<Self, String> {
+        let (mode, skip) = (settings.image_processing_mode(), settings.skip_images);
+        let mut images = Self { parts: HashMap::new(), media: HashMap::new(), mode, skip };
+        if skip {
+            return Ok(images);
         }
+        images.media = opc::read_parts_under(archive, "word/media/")?.into_iter().collect();
+        images.parts = opc::read_rels(archive, "word/document.xml")?
+            .into_iter()
+            .filter(|(_, rel)| !rel.external)
+            .map(|(id, rel)| (id, opc::resolve_target("word/document.xml", &rel.target)))
+            .collect();
+        Ok(images)
+    }
+
+    // (media part, bytes) behind a relationship id
+    fn get(&self, rel_id: &str) -> Option<(&str, &[u8])> {
+        let part = self.parts.get(rel_id)?;
+        self.media.get(part).map(|data| (part.as_str(), data.as_slice()))
     }
+}
+
+fn process_drawing_images_with_mode(
+    drawing: &docx_rust::document::Drawing,
+    images: &DocumentImages,
+) -> Result<Option<String>, String> {
+    // Alt text the author gave the picture (wp:docPr descr, else title)
+    let doc_property = drawing
+        .inline
+        .as_ref()
+        .map(|inline| &inline.doc_property)
+        .or_else(|| drawing.anchor.as_ref().map(|anchor| &anchor.doc_property));
+    let alt_text = doc_property.and_then(|property| {
+        [&property.descr, &property.title]
+            .into_iter()
+            .flatten()
+            .map(|text| text.trim())
+            .find(|text| !text.is_empty())
+    });
     
-    // If no output path configured or can't make relative, return as-is
-    Ok(markdown)
+    // The picture the drawing's blip points at
+    let graphic = drawing
+        .inline
+        .as_ref()
+        .and_then(|inline| inline.graphic.as_ref())
+        .or_else(|| drawing.anchor.as_ref().and_then(|anchor| anchor.graphic.as_ref()));
+    let image = graphic
+        .and_then(|graphic| graphic.data.children.first())
+        .and_then(|picture| images.get(&picture.fill.blip.embed));
+
+    if let Some((part, image_data)) = image {
+        let extension = part.rsplit('.').next().unwrap_or_default().to_ascii_lowercase();
+        if matches!(extension.as_str(), "png" | "jpg" | "jpeg" | "gif" | "webp") {
+            let image_md = image2md::run_with_alt(image_data, images.mode, alt_text)?;
+            return Ok(Some(format!("\n\n{}\n\n", image_md)));
+        }
+    }
+    if !images.skip {
+        crate::context::warn("Drawing dropped: no PNG/JPEG/GIF/WebP image to show for it");
+    }
+    Ok(None)
 }
 
 fn check_style_for_heading(style_name: &str) -> Option<(bool, usize)> {
@@ -365,26 +816,26 @@ fn determine_heading_status(
         };
         
         // Additional check: short lines are more likely to be headings
-        if content.trim().len() < 100 && !content.trim().ends_with('.') {
+        let trimmed = content.trim();
+        if text_width(trimmed) < 100 && !ends_sentence(trimmed) {
             return (true, level);
         }
     }
     
     // Heuristic: short, bold lines without periods might be headings
     let trimmed = content.trim();
+    let width = text_width(trimmed);
     if has_bold && 
-       trimmed.len() > 0 && 
-       trimmed.len() < 80 && 
-       !trimmed.ends_with('.') && 
-       !trimmed.ends_with('!') && 
-       !trimmed.ends_with('?') &&
+       width > 0 && 
+       width < 80 && 
+       !ends_sentence(trimmed) &&
        !trimmed.contains('\n') &&
        trimmed.chars().any(|c| c.is_alphabetic()) {
         
         // Guess level based on length
-        if trimmed.len() < 30 {
+        if width < 30 {
             return (true, 2); // Short titles are likely H2
-        } else if trimmed.len() < 50 {
+        } else if width < 50 {
             return (true, 3); // Medium titles are likely H3
         } else {
             return (true, 4); // Longer titles are likely H4
@@ -394,78 +845,299 @@ fn determine_heading_status(
     (false, 1)
 }
 
-fn process_table(table: &docx_rust::document::Table) -> Result<String, String> {
-    if table.rows.is_empty() {
-        return Ok(String::new());
-    }
+// Length in characters rather than UTF-8 bytes; CJK characters carry about a word
+// each, so they count double to keep the thresholds comparable with Latin text
+fn text_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if crate::stats::is_cjk(c) { 2 } else { 1 })
+        .sum()
+}
 
-    let mut markdown = String::new();
+// Sentence-final punctuation, including the CJK full-width and Arabic forms
+fn ends_sentence(text: &str) -> bool {
+    text.ends_with(['.', '!', '?', '。', '！', '？', '؟', '۔'])
+}
 
-    // Header row
-    markdown.push_str("|");
-    if let Some(first_row) = table.rows.first() {
-        for cell in &first_row.cells {
-            match cell {
-                TableRowContent::TableCell(tc) => {
-                    let cell_text = extract_cell_text(tc);
-                    markdown.push_str(&format!(" {} |", cell_text));
-                }
-                _ => {
-                    markdown.push_str(" |");
-                }
-            }
-        }
-        markdown.push_str("\n");
+fn process_table(table: &docx_rust::document::Table, settings: &Settings) -> Result<String, String> {
+    let cells: Vec<Vec<CellText>> = table.rows.iter().map(|row| table_row_cells(row, settings)).collect();
+    let alignments = column_alignments(&cells);
 
-        // Separator row
-        markdown.push_str("|");
-        for _ in &first_row.cells {
-            markdown.push_str("---|");
-        }
-        markdown.push_str("\n");
-    }
+    // A pipe table's first row is its header and renders bold already
+    let rows: Vec<Vec<String>> = cells
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            row.into_iter()
+                .map(|cell| {
+                    if cell.bold && index > 0 {
+                        cell.text.lines().map(|line| mark_change(line, "**", "**")).collect::<Vec<_>>().join("\n")
+                    } else {
+                        cell.text
+                    }
+                })
+                .collect()
+        })
+        .collect();
 
-    // Data rows
-    for row in table.rows.iter().skip(1) {
-        markdown.push_str("|");
-        for cell in &row.cells {
-            match cell {
-                TableRowContent::TableCell(tc) => {
-                    let cell_text = extract_cell_text(tc);
-                    markdown.push_str(&format!(" {} |", cell_text));
-                }
-                _ => {
-                    markdown.push_str(" |");
-                }
-            }
-        }
-        markdown.push_str("\n");
+    Ok(render_aligned_table(&rows, &alignments, settings.effective_table_style(), &settings.empty_cell_placeholder))
+}
+
+// Text of a table cell with inline `**...**` for bold runs; a cell that is bold
+// throughout is left unmarked with `bold` set instead
+#[derive(Default)]
+struct CellText {
+    text: String,
+    bold: bool,
+    alignment: Alignment,
+}
+
+// One entry per grid column: a `gridSpan` cell is followed by empty fillers and
+// the continuation of a vertical merge is left blank, so rows stay aligned
+fn table_row_cells(row: &docx_rust::document::TableRow, settings: &Settings) -> Vec<CellText> {
+    let mut cells = Vec::new();
+    for cell in &row.cells {
+        let TableRowContent::TableCell(tc) = cell else {
+            continue;
+        };
+        let span = tc
+            .property
+            .grid_span
+            .as_ref()
+            .and_then(|span| usize::try_from(span.val).ok())
+            .unwrap_or(1)
+            .max(1);
+        // `<w:vMerge/>` without a value continues the merge above
+        let merged_from_above = tc
+            .property
+            .v_merge
+            .as_ref()
+            .is_some_and(|merge| !matches!(merge.val, Some(VMergeType::Restart)));
+
+        cells.push(if merged_from_above { CellText::default() } else { extract_cell_text(tc, settings) });
+        cells.extend(std::iter::repeat_with(CellText::default).take(span - 1));
     }
+    cells
+}
 
-    Ok(markdown)
+// A column's alignment is the one all its body cells agree on (the header row
+// counts only for single-row tables); mixed or unset columns keep the default
+fn column_alignments(rows: &[Vec<CellText>]) -> Vec<Alignment> {
+    let body = if rows.len() > 1 { &rows[1..] } else { rows };
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    (0..width)
+        .map(|column| {
+            let mut alignments = body
+                .iter()
+                .filter_map(|row| row.get(column))
+                .filter(|cell| !cell.text.is_empty())
+                .map(|cell| cell.alignment);
+            let first = alignments.next().unwrap_or_default();
+            if alignments.all(|alignment| alignment == first) { first } else { Alignment::Default }
+        })
+        .collect()
 }
 
-fn extract_cell_text(cell: &docx_rust::document::TableCell) -> String {
-    let mut text = String::new();
-    
+fn extract_cell_text(cell: &docx_rust::document::TableCell, settings: &Settings) -> CellText {
+    // (bold, text) per paragraph, consecutive runs of the same weight merged
+    let mut paragraphs: Vec<Vec<(bool, String)>> = Vec::new();
+    let mut alignment = None;
+
     for content in &cell.content {
         match content {
             TableCellContent::Paragraph(paragraph) => {
+                alignment = alignment.or_else(|| paragraph_alignment(paragraph));
+                let mut segments: Vec<(bool, String)> = Vec::new();
                 for para_content in &paragraph.content {
-                    if let ParagraphContent::Run(run) = para_content {
-                        for run_content in &run.content {
-                            if let docx_rust::document::RunContent::Text(text_elem) = run_content {
-                                text.push_str(&text_elem.text);
-                            }
+                    let Some((runs, open, close)) = tracked_runs(para_content, settings.track_changes, settings.flavor) else {
+                        continue;
+                    };
+                    for run in runs {
+                        let bold = run
+                            .property
+                            .as_ref()
+                            .and_then(|props| props.bold.as_ref())
+                            .is_some_and(|bold| bold.value != Some(false));
+                        let text: String = run
+                            .content
+                            .iter()
+                            .filter_map(|run_content| match run_content {
+                                RunContent::Text(text_elem) => Some(&*text_elem.text),
+                                RunContent::DelText(text_elem) => Some(&*text_elem.text),
+                                RunContent::Break(_) => Some("\n"),
+                                _ => None,
+                            })
+                            .collect();
+                        if text.is_empty() {
+                            continue;
+                        }
+                        let text = mark_change(&text, open, close);
+                        match segments.last_mut() {
+                            Some((last_bold, last_text)) if *last_bold == bold => last_text.push_str(&text),
+                            _ => segments.push((bold, text)),
                         }
                     }
                 }
-                if !text.is_empty() && !text.ends_with(' ') {
-                    text.push(' ');
+                // One line per paragraph; pipe tables flatten them again
+                if segments.iter().any(|(_, text)| !text.trim().is_empty()) {
+                    paragraphs.push(segments);
                 }
             }
         }
     }
-    
-    text.trim().to_string()
+
+    let bold = !paragraphs.is_empty()
+        && paragraphs
+            .iter()
+            .flatten()
+            .all(|(bold, text)| *bold || text.trim().is_empty());
+    let text = paragraphs
+        .iter()
+        .map(|segments| {
+            segments
+                .iter()
+                .map(|(segment_bold, text)| {
+                    if *segment_bold && !bold { mark_change(text, "**", "**") } else { text.clone() }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    CellText {
+        text: text.trim().to_string(),
+        bold,
+        alignment: alignment.unwrap_or_default(),
+    }
+}
+
+// `w:jc` of a paragraph; justified text has no Markdown counterpart
+fn paragraph_alignment(paragraph: &docx_rust::document::Paragraph) -> Option<Alignment> {
+    let justification = paragraph.property.as_ref()?.justification.as_ref()?;
+    Some(match justification.value {
+        JustificationVal::Left | JustificationVal::Start => Alignment::Left,
+        JustificationVal::Center => Alignment::Center,
+        JustificationVal::Right | JustificationVal::End => Alignment::Right,
+        _ => Alignment::Default,
+    })
+}
+
+// Runs of a paragraph item that `mode` keeps, with the markup to wrap their text in;
+// None for dropped changes and for items that aren't runs
+fn tracked_runs<'r, 'a>(
+    content: &'r ParagraphContent<'a>,
+    mode: TrackChangesMode,
+    flavor: MarkdownFlavor,
+) -> Option<(&'r [Run<'a>], &'static str, &'static str)> {
+    match (content, mode) {
+        (ParagraphContent::Run(run), _) => Some((std::slice::from_ref(run), "", "")),
+        (ParagraphContent::Insertion(insertion), TrackChangesMode::AcceptAll) => Some((&insertion.runs, "", "")),
+        (ParagraphContent::Insertion(insertion), TrackChangesMode::ShowBoth) => Some((&insertion.runs, "<ins>", "</ins>")),
+        (ParagraphContent::Deletion(deletion), TrackChangesMode::RejectAll) => Some((&deletion.runs, "", "")),
+        (ParagraphContent::Deletion(deletion), TrackChangesMode::ShowBoth) => {
+            let (open, close) = flavor.strikethrough();
+            Some((&deletion.runs, open, close))
+        }
+        _ => None,
+    }
+}
+
+// Wrap the text inside any surrounding whitespace: `~~ old~~` wouldn't render as strikethrough
+fn mark_change(text: &str, open: &str, close: &str) -> String {
+    let trimmed = text.trim();
+    if open.is_empty() || trimmed.is_empty() {
+        return text.to_string();
+    }
+    let start = text.len() - text.trim_start().len();
+    let end = start + trimmed.len();
+    format!("{}{}{}{}{}", &text[..start], open, trimmed, close, &text[end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_item_per_flavor() {
+        assert_eq!(task_item("\u{2612} Ship it", true, '-', MarkdownFlavor::Gfm), "- [x] Ship it");
+        assert_eq!(task_item("Review", false, '*', MarkdownFlavor::CommonMark), "* \u{2610} Review");
+    }
+
+    #[test]
+    fn test_rtl_paragraphs() {
+        let xml = r#"<w:document><w:body>
+            <w:p><w:pPr><w:bidi/></w:pPr><w:r><w:t>مرحبا</w:t></w:r></w:p>
+            <w:p><w:r><w:rPr><w:rtl/></w:rPr><w:t>שלום</w:t></w:r></w:p>
+            <w:p><w:r><w:t>Hello </w:t></w:r><w:r><w:rPr><w:rtl/></w:rPr><w:t>שלום</w:t></w:r></w:p>
+            <w:p><w:pPr><w:bidi w:val="0"/></w:pPr><w:r><w:t>Hi</w:t></w:r></w:p>
+        </w:body></w:document>"#;
+        let rtl: Vec<bool> = scan_body_paragraphs(xml).iter().map(|p| p.rtl).collect();
+        assert_eq!(rtl, [true, true, false, false]);
+
+        assert_eq!(mark_rtl("## مقدمة", RtlMarkup::Mark), "## \u{200F}مقدمة");
+        assert_eq!(mark_rtl("- [x] تم", RtlMarkup::Mark), "- [x] \u{200F}تم");
+        assert_eq!(mark_rtl("שלום", RtlMarkup::Html), "<div dir=\"rtl\">\n\nשלום\n\n</div>");
+    }
+
+    #[test]
+    fn test_equations_are_placed_in_the_text() {
+        let xml = r#"<w:document><w:body>
+            <w:p><w:r><w:t xml:space="preserve">Area </w:t></w:r><m:oMath><m:r><m:t>π</m:t></m:r><m:sSup><m:e><m:r><m:t>r</m:t></m:r></m:e><m:sup><m:r><m:t>2</m:t></m:r></m:sup></m:sSup></m:oMath><w:r><w:t>.</w:t></w:r></w:p>
+            <w:p><m:oMathPara><m:oMath><m:sPre2/></m:oMath></m:oMathPara></w:p>
+            <w:p><w:r><w:t>No math</w:t></w:r></w:p>
+            <w:p><w:ins><w:r><w:t>new </w:t></w:r></w:ins><w:del><w:r><w:delText>old </w:delText></w:r></w:del><m:oMath><m:r><m:t>x</m:t></m:r></m:oMath></w:p>
+        </w:body></w:document>"#;
+        let paragraphs = scan_body_paragraphs(xml);
+        let area = Equation { run_index: 1, latex: Some("\\pi {r}^{2}".to_string()), display: false };
+        assert_eq!(paragraphs[0].equations, [area]);
+        assert_eq!(place_equations("Area \u{e}.", &paragraphs[0].equations), "Area $\\pi {r}^{2}$.");
+        assert_eq!(place_equations("\u{e}", &paragraphs[1].equations), EQUATION_PLACEHOLDER);
+        assert!(paragraphs[2].equations.is_empty());
+        // Runs of tracked changes count, whichever of them end up in the text
+        assert_eq!(paragraphs[3].equations[0].run_index, 2);
+        assert_eq!(place_equations("x\u{e}", &[Equation { run_index: 0, latex: Some("y".into()), display: true }]), "x\n\n$$\ny\n$$");
+    }
+
+    #[test]
+    fn test_line_breaks() {
+        assert_eq!(paragraph_part("\n12 Main St \nSpringfield\n", None), "12 Main St\\\nSpringfield");
+        assert_eq!(paragraph_part(" Part\nOne", Some(2)), "## Part One");
+        assert_eq!(paragraph_part("plain ", None), "plain ");
+    }
+
+    #[test]
+    fn test_heading_heuristic_counts_characters() {
+        // 12 kanji are 36 bytes but a short title
+        assert_eq!(determine_heading_status(false, 1, true, None, "第三章の概要と今後の計画"), (true, 2));
+        // A full Japanese sentence is not a heading
+        assert_eq!(determine_heading_status(false, 1, true, None, "これは見出しではありません。"), (false, 1));
+        // Arabic question mark ends a sentence
+        assert_eq!(determine_heading_status(false, 1, true, None, "هل هذا عنوان؟"), (false, 1));
+        assert_eq!(determine_heading_status(false, 1, true, None, "مقدمة الكتاب"), (true, 2));
+    }
+
+    #[test]
+    fn test_mark_change_keeps_whitespace_outside() {
+        assert_eq!(mark_change(" old text ", "~~", "~~"), " ~~old text~~ ");
+        assert_eq!(mark_change("new", "<ins>", "</ins>"), "<ins>new</ins>");
+        assert_eq!(mark_change("plain ", "", ""), "plain ");
+    }
+
+    #[test]
+    fn test_mark_pandoc_changes() {
+        let markdown = r#"Total <span class="deletion" author="Ann" date="2024-01-02T00:00:00Z">10 </span><span class="insertion" author="Ann">12</span> units<span class="paragraph-insertion" author="Ann"></span>"#;
+        assert_eq!(mark_pandoc_changes(markdown, MarkdownFlavor::Gfm), "Total ~~10~~ <ins>12</ins> units");
+        assert_eq!(
+            mark_pandoc_changes(markdown, MarkdownFlavor::CommonMark),
+            "Total <del>10</del> <ins>12</ins> units"
+        );
+    }
+
+    // Pandoc's own output, as `run_with_pandoc` gets it with skip_images; the full
+    // pandoc path only runs where pandoc is installed
+    #[test]
+    fn test_remove_image_links() {
+        let markdown = "Before <img src=\"media/image1.png\"\nstyle=\"width:1in\" /> after\n\n![](media/image2.jpeg)\n";
+        assert_eq!(remove_image_links(markdown), "Before  after\n\n\n");
+        assert_eq!(remove_image_links("[a link](https://example.com)"), "[a link](https://example.com)");
+    }
 }
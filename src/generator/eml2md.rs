@@ -0,0 +1,158 @@
+use mail_parser::{Address, MessageParser, MessagePart, MimeHeaders};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use crate::config::SETTINGS;
+use crate::context;
+use crate::generator::{html2md, image2md};
+
+pub fn run(file_stream: &[u8]) -> Result<String, String> {
+    let message = MessageParser::default()
+        .parse(file_stream)
+        .ok_or_else(|| "Failed to parse email message".to_string())?;
+
+    let mut markdown = format!(
+        "# {}\n\n",
+        message.subject().filter(|s| !s.trim().is_empty()).unwrap_or("(no subject)").trim()
+    );
+
+    // Metadata block
+    let bullet = SETTINGS.read().unwrap().bullet_char;
+    let headers = [
+        ("From", message.from()),
+        ("To", message.to()),
+        ("Cc", message.cc()),
+    ];
+    for (label, address) in headers {
+        if let Some(address) = address.map(format_address).filter(|a| !a.is_empty()) {
+            markdown.push_str(&format!("{} **{}:** {}\n", bullet, label, address));
+        }
+    }
+    if let Some(date) = message.date() {
+        let date = chrono::DateTime::from_timestamp(date.to_timestamp(), 0)
+            .map(crate::dates::format_timestamp)
+            .unwrap_or_else(|| date.to_rfc3339());
+        markdown.push_str(&format!("{} **Date:** {}\n", bullet, date));
+    }
+    markdown.push_str("\n---\n\n");
+
+    // Inline images referenced from the HTML body as `cid:...`
+    let mut inline_images = HashMap::new();
+    for part in message.attachments() {
+        if let Some(content_id) = part.content_id().filter(|_| is_image(part)) {
+            inline_images.insert(content_id.trim_matches(['<', '>']).to_string(), part.contents());
+        }
+    }
+
+    // Prefer the HTML body, which keeps links and formatting
+    let body = match message.body_html(0) {
+        Some(html) => {
+            let body = html2md::run(html.as_bytes())?;
+            resolve_cid_images(&body, &inline_images)?
+        }
+        None => message.body_text(0).map(|text| text.into_owned()).unwrap_or_default(),
+    };
+    markdown.push_str(body.trim());
+    markdown.push_str("\n\n");
+
+    // cid images already appear in the body
+    let is_listed = |part: &&MessagePart| {
+        part.content_id()
+            .map(|id| !inline_images.contains_key(id.trim_matches(['<', '>'])))
+            .unwrap_or(true)
+    };
+    let attachments: Vec<String> = message
+        .attachments()
+        .filter(is_listed)
+        .map(|part| {
+            let name = part
+                .attachment_name()
+                .map(str::to_string)
+                .or_else(|| part.message().and_then(|m| m.subject()).map(|s| format!("Forwarded: {}", s)))
+                .unwrap_or_else(|| "(unnamed)".to_string());
+            let content_type = part
+                .content_type()
+                .map(|ct| match ct.subtype() {
+                    Some(subtype) => format!("{}/{}", ct.ctype(), subtype),
+                    None => ct.ctype().to_string(),
+                })
+                .unwrap_or_else(|| "application/octet-stream".to_string());
+            format!("{} {} ({}, {})", bullet, name, content_type, format_size(part.len()))
+        })
+        .collect();
+
+    if !attachments.is_empty() {
+        markdown.push_str("## Attachments\n\n");
+        markdown.push_str(&attachments.join("\n"));
+        markdown.push('\n');
+    }
+
+    // Attached pictures are shown too, with the file name as alt text
+    let mode = SETTINGS.read().unwrap().image_processing_mode();
+    for part in message.attachments().filter(is_listed).filter(|part| is_image(part)) {
+        match image2md::run_with_alt(part.contents(), mode, part.attachment_name()) {
+            Ok(image_md) if !image_md.trim().is_empty() => {
+                markdown.push_str(&format!("\n{}\n", image_md.trim()));
+            }
+            Ok(_) => {}
+            Err(e) => context::warn(format!(
+                "Attachment {} could not be shown: {}",
+                part.attachment_name().unwrap_or("(unnamed)"),
+                e
+            )),
+        }
+    }
+
+    Ok(markdown)
+}
+
+fn is_image(part: &MessagePart) -> bool {
+    part.content_type().is_some_and(|ct| ct.ctype().eq_ignore_ascii_case("image"))
+}
+
+fn format_address(address: &Address) -> String {
+    address
+        .iter()
+        .map(|addr| match (addr.name(), addr.address()) {
+            (Some(name), Some(email)) => format!("{} <{}>", name, email),
+            (None, Some(email)) => email.to_string(),
+            (Some(name), None) => name.to_string(),
+            (None, None) => String::new(),
+        })
+        .filter(|addr| !addr.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Replace `![alt](cid:id)` with the image2md rendering of that MIME part
+fn resolve_cid_images(markdown: &str, inline_images: &HashMap<String, &[u8]>) -> Result<String, String> {
+    if inline_images.is_empty() {
+        return Ok(markdown.to_string());
+    }
+
+    let cid_image = Regex::new(r"!\[[^\]]*\]\(cid:([^)\s]+)\)").unwrap();
+    let mut error = None;
+    let resolved = cid_image.replace_all(markdown, |caps: &Captures| {
+        match inline_images.get(&caps[1]) {
+            Some(data) => image2md::run(data).unwrap_or_else(|e| {
+                error.get_or_insert(e);
+                String::new()
+            }),
+            None => caps[0].to_string(),
+        }
+    });
+
+    match error {
+        Some(e) => Err(format!("Failed to convert inline image: {}", e)),
+        None => Ok(resolved.into_owned()),
+    }
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
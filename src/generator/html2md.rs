@@ -1,16 +1,363 @@
 use html2md::parse_html;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use crate::codelang::guess_fence_language;
+use crate::config::SETTINGS;
+use crate::markdown::MarkdownBuilder;
+
+// Stands in for a <pre> block while html2md runs; plain letters/digits so it survives unescaped
+const CODE_PLACEHOLDER: &str = "MARKITUPCODEBLOCK";
+// Same for a `preserve_raw_html` subtree
+const RAW_HTML_PLACEHOLDER: &str = "MARKITUPRAWHTML";
+// Same for a table rendered by `render_table`
+const TABLE_PLACEHOLDER: &str = "MARKITUPTABLE";
+
+// Elements that never have a closing tag
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<(/?)([a-zA-Z][a-zA-Z0-9-]*)([^>]*)>").unwrap());
+// `display: table|table-row|table-cell` (not table-row-group etc.)
+static CSS_DISPLAY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)display\s*:\s*table(-row|-cell)?\s*(?:[;"'!]|$)"#).unwrap());
+static ARIA_ROLE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\brole\s*=\s*["']?(table|grid|row|cell|gridcell|columnheader|rowheader)\b"#).unwrap()
+});
+
+/// What an element is to the table walker
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TablePart {
+    Table,
+    Row,
+    Cell,
+    Other,
+}
 
 pub fn run(bytes: &[u8]) -> Result<String, String> {
     // Convert bytes to string
     let html_content = String::from_utf8(bytes.to_vec())
         .map_err(|e| format!("Invalid UTF-8 encoding: {}", e))?;
     
+    // Elements without a Markdown equivalent the user wants kept as they are
+    let preserved_tags = SETTINGS.read().unwrap().preserve_raw_html.clone();
+    let (html_content, raw_blocks) = extract_raw_html(&html_content, &preserved_tags);
+
+    // html2md drops or flattens many tables (and knows nothing of CSS tables),
+    // so they are rendered here; before code blocks so a <pre> in a cell stays in its cell
+    let (html_content, tables) = extract_tables(&html_content);
+
+    // html2md doesn't reliably keep <pre> whitespace or language hints,
+    // so code blocks are cut out first and put back as fenced blocks
+    let (html_content, code_blocks) = extract_code_blocks(&html_content);
+
     // Parse HTML to Markdown
     let markdown = parse_html(&html_content);
     
-    if markdown.trim().is_empty() {
+    if markdown.trim().is_empty() && raw_blocks.is_empty() {
         return Err("Empty or invalid HTML content".to_string());
     }
     
-    Ok(markdown)
+    let markdown = restore_blocks(&markdown, CODE_PLACEHOLDER, &code_blocks);
+    let markdown = restore_blocks(&markdown, TABLE_PLACEHOLDER, &tables);
+    Ok(restore_blocks(&markdown, RAW_HTML_PLACEHOLDER, &raw_blocks))
+}
+
+// Replace every outermost element named in `tags` (nesting-aware, case-insensitive)
+// with a placeholder paragraph; returns the verbatim subtrees. Unclosed elements stay put.
+fn extract_raw_html(html: &str, tags: &[String]) -> (String, Vec<String>) {
+    let mut html = html.to_string();
+    let mut blocks: Vec<String> = Vec::new();
+    let nested_placeholder = Regex::new(&format!(r"<p>{}(\d+)</p>", RAW_HTML_PLACEHOLDER)).unwrap();
+
+    for tag in tags.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+        let boundary = Regex::new(&format!(r"(?i)<(/?){}\b[^>]*>", regex::escape(tag))).unwrap();
+        let mut replaced = String::new();
+        let mut copied = 0;
+        let mut start = 0;
+        let mut depth = 0;
+
+        for caps in boundary.captures_iter(&html) {
+            let whole = caps.get(0).unwrap();
+            if caps[1].is_empty() {
+                if whole.as_str().ends_with("/>") {
+                    continue;
+                }
+                if depth == 0 {
+                    start = whole.start();
+                }
+                depth += 1;
+            } else if depth > 0 {
+                depth -= 1;
+                if depth == 0 {
+                    // A subtree cut out for an earlier tag may sit inside this one
+                    let block = nested_placeholder
+                        .replace_all(&html[start..whole.end()], |caps: &Captures| {
+                            caps[1]
+                                .parse::<usize>()
+                                .ok()
+                                .and_then(|index| blocks.get(index))
+                                .cloned()
+                                .unwrap_or_default()
+                        })
+                        .into_owned();
+                    replaced.push_str(&html[copied..start]);
+                    replaced.push_str(&format!("<p>{}{}</p>", RAW_HTML_PLACEHOLDER, blocks.len()));
+                    blocks.push(block);
+                    copied = whole.end();
+                }
+            }
+        }
+
+        replaced.push_str(&html[copied..]);
+        html = replaced;
+    }
+
+    (html, blocks)
+}
+
+// Replace every outermost table (`<table>`, or `display: table` / `role="table"` divs)
+// with a placeholder paragraph; returns the rendered tables. Tables nested in a cell
+// stay part of that cell's text, and an unclosed table is left to html2md.
+fn extract_tables(html: &str) -> (String, Vec<String>) {
+    let mut replaced = String::new();
+    let mut tables = Vec::new();
+    let mut copied = 0;
+    // Elements open inside the current outermost table, outermost first
+    let mut open: Vec<(String, TablePart)> = Vec::new();
+    let mut table_start = 0;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut cell_start: Option<usize> = None;
+
+    for caps in TAG.captures_iter(html) {
+        let whole = caps.get(0).unwrap();
+        let name = caps[2].to_lowercase();
+        let closing = !caps[1].is_empty();
+
+        if open.is_empty() {
+            if !closing && table_part(&name, &caps[3]) == TablePart::Table {
+                table_start = whole.start();
+                rows.clear();
+                cell_start = None;
+                open.push((name, TablePart::Table));
+            }
+            continue;
+        }
+
+        let nested = open.iter().filter(|(_, part)| *part == TablePart::Table).count() > 1;
+        if !closing {
+            if VOID_ELEMENTS.contains(&name.as_str()) || caps[3].ends_with('/') {
+                continue;
+            }
+            let part = table_part(&name, &caps[3]);
+            if !nested && matches!(part, TablePart::Row | TablePart::Cell) {
+                // `</td>` and `</tr>` are optional: a new row or cell ends the open cell
+                finish_cell(html, whole.start(), &mut cell_start, &mut rows);
+                if part == TablePart::Row || rows.is_empty() {
+                    rows.push(Vec::new());
+                }
+                if part == TablePart::Cell {
+                    cell_start = Some(whole.end());
+                }
+            }
+            open.push((name, part));
+            continue;
+        }
+
+        // A stray closing tag matches nothing and is ignored
+        let Some(index) = open.iter().rposition(|(open_name, _)| *open_name == name) else {
+            continue;
+        };
+        let closes_cell = open[index..].iter().any(|(_, part)| matches!(part, TablePart::Row | TablePart::Cell));
+        open.truncate(index);
+        if (!nested && closes_cell) || open.is_empty() {
+            finish_cell(html, whole.start(), &mut cell_start, &mut rows);
+        }
+        if open.is_empty() {
+            replaced.push_str(&html[copied..table_start]);
+            replaced.push_str(&format!("<p>{}{}</p>", TABLE_PLACEHOLDER, tables.len()));
+            tables.push(render_rows(std::mem::take(&mut rows)));
+            copied = whole.end();
+        }
+    }
+
+    replaced.push_str(&html[copied..]);
+    (replaced, tables)
+}
+
+fn table_part(name: &str, attributes: &str) -> TablePart {
+    match name {
+        "table" => return TablePart::Table,
+        "tr" => return TablePart::Row,
+        "td" | "th" => return TablePart::Cell,
+        _ => {}
+    }
+    if let Some(caps) = CSS_DISPLAY.captures(attributes) {
+        return match caps.get(1).map(|suffix| suffix.as_str().to_lowercase()).as_deref() {
+            None => TablePart::Table,
+            Some("-row") => TablePart::Row,
+            _ => TablePart::Cell,
+        };
+    }
+    match ARIA_ROLE.captures(attributes).map(|caps| caps[1].to_lowercase()).as_deref() {
+        Some("table" | "grid") => TablePart::Table,
+        Some("row") => TablePart::Row,
+        Some(_) => TablePart::Cell,
+        None => TablePart::Other,
+    }
+}
+
+// The open cell's HTML, up to `end`, converted and added to the last row
+fn finish_cell(html: &str, end: usize, cell_start: &mut Option<usize>, rows: &mut [Vec<String>]) {
+    if let (Some(start), Some(row)) = (cell_start.take(), rows.last_mut()) {
+        row.push(parse_html(&html[start..end]).trim().to_string());
+    }
+}
+
+fn render_rows(rows: Vec<Vec<String>>) -> String {
+    let rows: Vec<Vec<String>> = rows.into_iter().filter(|row| !row.is_empty()).collect();
+    // A single column is a layout table: its cells are just content stacked up
+    if rows.iter().all(|row| row.len() <= 1) {
+        return rows
+            .into_iter()
+            .flatten()
+            .filter(|cell| !cell.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+    }
+    let mut md = MarkdownBuilder::new();
+    md.table(&rows[0], &rows[1..]);
+    md.finish()
+}
+
+// Replace every <pre> with a numbered placeholder paragraph; returns the fenced blocks
+fn extract_code_blocks(html: &str) -> (String, Vec<String>) {
+    let pre = Regex::new(r"(?is)<pre\b([^>]*)>(.*?)</pre\s*>").unwrap();
+    let mut blocks = Vec::new();
+
+    let replaced = pre.replace_all(html, |caps: &Captures| {
+        let fence = fenced_block(&caps[1], &caps[2]);
+        let placeholder = format!("<p>{}{}</p>", CODE_PLACEHOLDER, blocks.len());
+        blocks.push(fence);
+        placeholder
+    });
+
+    (replaced.into_owned(), blocks)
+}
+
+// Put the cut-out blocks back in place of their `<placeholder><index>` markers
+fn restore_blocks(markdown: &str, placeholder: &str, blocks: &[String]) -> String {
+    if blocks.is_empty() {
+        return markdown.to_string();
+    }
+    let placeholder = Regex::new(&format!(r"{}(\d+)", placeholder)).unwrap();
+    placeholder
+        .replace_all(markdown, |caps: &Captures| {
+            caps[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| blocks.get(index))
+                .cloned()
+                .unwrap_or_default()
+        })
+        .into_owned()
+}
+
+fn fenced_block(pre_attributes: &str, inner_html: &str) -> String {
+    let language_class = Regex::new(r#"(?i)class\s*=\s*["'][^"']*?\b(?:language|lang)-([\w+#.-]+)"#).unwrap();
+    // The hint may sit on <pre> or on the inner <code>
+    let language = language_class
+        .captures(pre_attributes)
+        .or_else(|| language_class.captures(inner_html))
+        .map(|caps| caps[1].to_lowercase());
+
+    // Drop <code> and highlighter <span>s, keep the text byte-for-byte
+    let tag = Regex::new(r"(?s)<[^>]+>").unwrap();
+    let code = decode_entities(&tag.replace_all(inner_html, ""));
+    // A newline right after <pre> is ignored by HTML
+    let code = code.strip_prefix("\r\n").or_else(|| code.strip_prefix('\n')).unwrap_or(&code);
+    let code = code.trim_end_matches(['\n', '\r']);
+
+    // No class hint: fall back to guessing from the code itself
+    let language = language
+        .or_else(|| guess_fence_language(code).map(str::to_string))
+        .unwrap_or_default();
+
+    // The fence must be longer than any backtick run inside the code
+    let longest_run = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+
+    format!("{}{}\n{}\n{}", fence, language, code, fence)
+}
+
+fn decode_entities(text: &str) -> String {
+    let entity = Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+    entity
+        .replace_all(text, |caps: &Captures| {
+            let name = &caps[1];
+            let decoded = match name {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{00A0}'),
+                _ if name.starts_with("#x") || name.starts_with("#X") => {
+                    u32::from_str_radix(&name[2..], 16).ok().and_then(char::from_u32)
+                }
+                _ if name.starts_with('#') => name[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            decoded.map(String::from).unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pre_becomes_fenced_block() {
+        let html = "<h1>Post</h1><pre><code class=\"language-rust\">fn main() {\n    if a &lt; b { println!(\"&amp;\"); }\n}\n</code></pre><p>after</p>";
+        let markdown = run(html.as_bytes()).unwrap();
+        assert!(markdown.contains(
+            "```rust\nfn main() {\n    if a < b { println!(\"&\"); }\n}\n```"
+        ));
+        assert!(markdown.contains("after"));
+    }
+
+    #[test]
+    fn test_tables_are_rendered_through_render_table() {
+        let html = "<p>Prices</p><table><tr><th>Item<th>Price\
+                    <tr><td><a href=\"https://example.com/tea\">Tea</a></td><td>1.50</td></tr></table>\
+                    <div style=\"display: table\"><div style=\"display:table-row\"><span style=\"display: table-cell\">A</span>\
+                    <span style=\"display: table-cell\">B</span></div><div role=\"row\"><div role=\"cell\">1</div>\
+                    <div role=\"cell\">2</div></div></div>";
+        let (stripped, tables) = extract_tables(html);
+        assert_eq!(stripped, "<p>Prices</p><p>MARKITUPTABLE0</p><p>MARKITUPTABLE1</p>");
+        assert!(tables[0].contains("| Item | Price |"));
+        assert!(tables[0].contains("| [Tea](https://example.com/tea) | 1.50 |"));
+        assert!(tables[1].contains("| A | B |\n|---|---|\n| 1 | 2 |"));
+    }
+
+    #[test]
+    fn test_preserved_tags_pass_through_verbatim() {
+        let html = "<p>intro</p><DETAILS open><summary>More</summary><details><p>inner</p></details></DETAILS>\
+                    <figure><img src=\"a.png\"><figcaption>Fig</figcaption></figure>";
+        let tags = vec!["details".to_string(), "figure".to_string()];
+        let (stripped, blocks) = extract_raw_html(html, &tags);
+        assert_eq!(stripped, "<p>intro</p><p>MARKITUPRAWHTML0</p><p>MARKITUPRAWHTML1</p>");
+        assert_eq!(
+            blocks,
+            vec![
+                "<DETAILS open><summary>More</summary><details><p>inner</p></details></DETAILS>".to_string(),
+                "<figure><img src=\"a.png\"><figcaption>Fig</figcaption></figure>".to_string(),
+            ]
+        );
+    }
 }
@@ -0,0 +1,285 @@
+// iCalendar VEVENTs -> an agenda of `### Summary` sections
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use super::vcard2md::unfold_lines;
+use crate::config::SETTINGS;
+use crate::dates;
+
+#[derive(Default)]
+struct CalendarEvent {
+    summary: Option<String>,
+    start: Option<EventTime>,
+    end: Option<EventTime>,
+    location: Option<String>,
+    description: Option<String>,
+    rrule: Option<String>,
+}
+
+#[derive(Clone, PartialEq)]
+enum EventTime {
+    // VALUE=DATE (all-day)
+    Date(NaiveDate),
+    // UTC (`Z` suffix): shown in `Settings.timezone`
+    Instant(DateTime<Utc>),
+    // floating or TZID time: kept as written, with its TZID label
+    DateTime(NaiveDateTime, Option<String>),
+}
+
+pub fn run(file_stream: &[u8]) -> Result<String, String> {
+    let content = String::from_utf8_lossy(file_stream);
+    let mut events = parse_events(&content);
+    if events.is_empty() {
+        return Err("No events found in calendar".to_string());
+    }
+
+    // Agenda order; events without a start keep their position at the end
+    events.sort_by(|a, b| match (&a.start, &b.start) {
+        (Some(a), Some(b)) => sort_key(a).cmp(&sort_key(b)),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+
+    let mut markdown = String::new();
+    for event in &events {
+        markdown.push_str(&render_event(event));
+        markdown.push('\n');
+    }
+    Ok(markdown)
+}
+
+fn parse_events(content: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<CalendarEvent> = None;
+    // VALARM and friends nest inside VEVENT; their properties aren't the event's
+    let mut nested_depth = 0usize;
+
+    for line in unfold_lines(content) {
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut params = head.split(';');
+        let name = params.next().unwrap_or_default().to_uppercase();
+        let params: Vec<&str> = params.collect();
+
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some(CalendarEvent::default());
+                nested_depth = 0;
+            }
+            "END" if value.eq_ignore_ascii_case("VEVENT") => events.extend(current.take()),
+            "BEGIN" if current.is_some() => nested_depth += 1,
+            "END" if current.is_some() => nested_depth = nested_depth.saturating_sub(1),
+            _ => {
+                let Some(event) = current.as_mut() else {
+                    continue;
+                };
+                if nested_depth > 0 {
+                    continue;
+                }
+                match name.as_str() {
+                    "SUMMARY" => event.summary = Some(unescape(value)),
+                    "LOCATION" => event.location = Some(unescape(value)),
+                    "DESCRIPTION" => event.description = Some(unescape(value)),
+                    "DTSTART" => event.start = parse_time(value, &params),
+                    "DTEND" => event.end = parse_time(value, &params),
+                    "RRULE" => event.rrule = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    events
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => {}
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result.trim().to_string()
+}
+
+fn parse_time(value: &str, params: &[&str]) -> Option<EventTime> {
+    let value = value.trim();
+    let is_date = params.iter().any(|param| param.eq_ignore_ascii_case("VALUE=DATE"))
+        || value.len() == 8;
+    if is_date {
+        return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(EventTime::Date);
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(EventTime::Instant(Utc.from_utc_datetime(&naive)));
+    }
+
+    let tzid = params
+        .iter()
+        .find_map(|param| param.split_once('=').filter(|(key, _)| key.eq_ignore_ascii_case("TZID")))
+        .map(|(_, tzid)| tzid.trim_matches('"').to_string());
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|naive| EventTime::DateTime(naive, tzid))
+}
+
+fn sort_key(time: &EventTime) -> NaiveDateTime {
+    match time {
+        EventTime::Date(date) => date.and_hms_opt(0, 0, 0).unwrap_or_default(),
+        EventTime::Instant(instant) => dates::in_configured_zone(*instant),
+        EventTime::DateTime(naive, _) => *naive,
+    }
+}
+
+fn format_time(time: &EventTime) -> String {
+    match time {
+        EventTime::Date(date) => dates::format_date(*date),
+        EventTime::Instant(instant) => dates::format_timestamp(*instant),
+        EventTime::DateTime(naive, None) => dates::format_naive(*naive),
+        EventTime::DateTime(naive, Some(tzid)) => format!("{} ({})", dates::format_naive(*naive), tzid),
+    }
+}
+
+// `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10` -> "every 2 weeks on Mon, Wed, 10 times"
+fn describe_rrule(rrule: &str) -> String {
+    let mut freq = None;
+    let mut interval = 1u32;
+    let mut by_day = Vec::new();
+    let mut count = None;
+    let mut until = None;
+
+    for part in rrule.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.to_uppercase().as_str() {
+            "FREQ" => freq = Some(value.to_uppercase()),
+            "INTERVAL" => interval = value.parse().unwrap_or(1),
+            "BYDAY" => by_day = value.split(',').map(weekday_name).collect(),
+            "COUNT" => count = value.parse::<u32>().ok(),
+            "UNTIL" => {
+                let date = value.get(..8).unwrap_or(value);
+                until = Some(
+                    NaiveDate::parse_from_str(date, "%Y%m%d")
+                        .map(dates::format_date)
+                        .unwrap_or_else(|_| value.to_string()),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let unit = match freq.as_deref() {
+        Some("DAILY") => "day",
+        Some("WEEKLY") => "week",
+        Some("MONTHLY") => "month",
+        Some("YEARLY") => "year",
+        _ => return rrule.to_string(),
+    };
+
+    let mut description = if interval > 1 {
+        format!("every {} {}s", interval, unit)
+    } else {
+        format!("every {}", unit)
+    };
+    if !by_day.is_empty() {
+        description.push_str(&format!(" on {}", by_day.join(", ")));
+    }
+    if let Some(count) = count {
+        description.push_str(&format!(", {} times", count));
+    }
+    if let Some(until) = until {
+        description.push_str(&format!(" until {}", until));
+    }
+    description
+}
+
+// `MO` / `2TU` / `-1FR`: the ordinal prefix is kept as-is
+fn weekday_name(day: &str) -> String {
+    let split = day.len().saturating_sub(2);
+    let (ordinal, code) = day.split_at(split);
+    let name = match code.to_uppercase().as_str() {
+        "MO" => "Mon",
+        "TU" => "Tue",
+        "WE" => "Wed",
+        "TH" => "Thu",
+        "FR" => "Fri",
+        "SA" => "Sat",
+        "SU" => "Sun",
+        _ => code,
+    };
+    format!("{}{}", ordinal, name)
+}
+
+fn render_event(event: &CalendarEvent) -> String {
+    let summary = event
+        .summary
+        .as_deref()
+        .filter(|summary| !summary.is_empty())
+        .unwrap_or("Untitled event");
+    let mut markdown = format!("### {}\n\n", summary);
+    let bullet = SETTINGS.read().unwrap().bullet_char;
+
+    match (&event.start, &event.end) {
+        (Some(start), Some(end)) => {
+            markdown.push_str(&format!("{} **When:** {} – {}\n", bullet, format_time(start), format_time(end)));
+        }
+        (Some(start), None) => markdown.push_str(&format!("{} **When:** {}\n", bullet, format_time(start))),
+        _ => {}
+    }
+    if let Some(rrule) = &event.rrule {
+        markdown.push_str(&format!("{} **Repeats:** {}\n", bullet, describe_rrule(rrule)));
+    }
+    if let Some(location) = event.location.as_deref().filter(|location| !location.is_empty()) {
+        markdown.push_str(&format!("{} **Location:** {}\n", bullet, location));
+    }
+    if let Some(description) = event.description.as_deref().filter(|description| !description.is_empty()) {
+        markdown.push('\n');
+        markdown.push_str(description);
+        markdown.push('\n');
+    }
+
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_rrule() {
+        assert_eq!(
+            describe_rrule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10"),
+            "every 2 weeks on Mon, Wed, 10 times"
+        );
+        assert_eq!(
+            describe_rrule("FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20241231T000000Z"),
+            "every month on -1Fri until 2024-12-31"
+        );
+    }
+
+    #[test]
+    fn test_events_skip_alarms() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Standup\r\n\
+                   DTSTART;TZID=Europe/Berlin:20240501T090000\r\nDTEND;TZID=Europe/Berlin:20240501T091500\r\n\
+                   LOCATION:Room 1\r\nBEGIN:VALARM\r\nDESCRIPTION:Reminder\r\nEND:VALARM\r\n\
+                   END:VEVENT\r\nBEGIN:VEVENT\r\nSUMMARY:Offsite\r\nDTSTART;VALUE=DATE:20240401\r\n\
+                   DESCRIPTION:Bring\\, laptops\\nand chargers\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let markdown = run(ics.as_bytes()).unwrap();
+        assert_eq!(
+            markdown,
+            "### Offsite\n\n- **When:** 2024-04-01\n\nBring, laptops\nand chargers\n\n\
+             ### Standup\n\n- **When:** 2024-05-01 09:00 (Europe/Berlin) – 2024-05-01 09:15 (Europe/Berlin)\n\
+             - **Location:** Room 1\n\n"
+        );
+    }
+}
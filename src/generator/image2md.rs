@@ -1,30 +1,59 @@
-use crate::config::SETTINGS;
+use crate::config::{Settings, SETTINGS};
+use crate::converter::{gif2png, svg2png};
 use base64::Engine;
 use std::fs;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use serde::{Deserialize, Serialize};
 
+/// Where images go: inline data URIs or files under `Settings.image_path`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ImageProcessingMode {
     Base64,
     SaveToFile,
 }
 
+/// How SaveToFile images are linked from the Markdown (`Settings.image_link_style`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageLinkStyle {
+    /// Relative to the output file's directory (`../images/x.png` works too); the file name without an output file
+    Relative,
+    /// Absolute path inside the (canonicalized) image directory
+    Absolute,
+    /// Just the file name
+    FilenameOnly,
+}
+
 
 pub fn run(file_stream: &[u8]) -> Result<String, String> {
-    let cfg = &*SETTINGS.read().unwrap();
-    
-    // Determine mode based on global config: if image_path is empty, use base64
-    let mode = if cfg.image_path.as_os_str().is_empty() {
-        ImageProcessingMode::Base64
-    } else {
-        ImageProcessingMode::SaveToFile
-    };
-    
+    let mode = SETTINGS.read().unwrap().image_processing_mode();
     run_with_mode(file_stream, mode)
 }
 
 
 pub fn run_with_mode(file_stream: &[u8], mode: ImageProcessingMode) -> Result<String, String> {
+    run_with_alt(file_stream, mode, None)
+}
+
+
+/// `run_with_mode` for an image that carries its own alt text (DOCX/PPTX `descr`/`title`),
+/// which is used instead of the AI description or the measured one
+pub fn run_with_alt(
+    file_stream: &[u8],
+    mode: ImageProcessingMode,
+    document_alt: Option<&str>,
+) -> Result<String, String> {
     let cfg = &*SETTINGS.read().unwrap();
 
+    // Text-only output: no base64, no saved files, no API calls
+    if cfg.skip_images {
+        return Ok(String::new());
+    }
+
     if file_stream.is_empty() {
         return Err("Input stream is empty".to_string());
     }
@@ -37,31 +66,80 @@ pub fn run_with_mode(file_stream: &[u8], mode: ImageProcessingMode) -> Result<St
             "image/png" => "png",
             "image/gif" => "gif",
             "image/webp" => "webp",
-            _ => "jpg", // default fallback
+            other => {
+                // default fallback
+                crate::context::warn(format!("No file extension known for {}, saved as .jpg", other));
+                "jpg"
+            }
         };
         (mime, ext)
+    } else if is_svg(file_stream) {
+        ("image/svg+xml".to_string(), "svg")
+    } else if cfg.strict {
+        return Err("Unrecognised image format".to_string());
     } else {
         ("image/jpeg".to_string(), "jpg")
     };
 
-    let image_name = if cfg.is_ai_enpower {
-        ai_generate_name_from_bytes(file_stream, &mime_type)
+    // Optionally swap the vector image for a PNG rendering of it
+    let rasterized;
+    let (file_stream, mime_type, extension) = if extension == "svg" && cfg.rasterize_svg {
+        rasterized = svg2png::svg_to_png(file_stream)?;
+        (rasterized.as_slice(), "image/png".to_string(), "png")
+    } else {
+        (file_stream, mime_type, extension)
+    };
+
+    // Animated GIFs are noted in the alt text, and optionally cut down to their first frame
+    let frames = if extension == "gif" { gif2png::frame_count(file_stream) } else { 0 };
+    let first_frame;
+    let (file_stream, mime_type, extension) = if frames > 1 && cfg.gif_first_frame_only {
+        first_frame = gif2png::first_frame_png(file_stream)?;
+        (first_frame.as_slice(), "image/png".to_string(), "png")
     } else {
-        // generate a timestamp-based name
-        let timestamp = chrono::Utc::now().timestamp();
-        format!("pic-{}", timestamp)
+        (file_stream, mime_type, extension)
     };
 
+    // The document's own alt text wins; then the AI description; otherwise
+    // the alt text describes what we can measure
+    let ai_name = cfg
+        .is_ai_enpower
+        .then(|| ai_generate_name_from_bytes(file_stream, &mime_type));
+    let document_alt = document_alt
+        .map(|alt| alt.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|alt| !alt.is_empty());
+    let mut alt_text = match (document_alt, &ai_name) {
+        (Some(alt), _) => alt.replace('[', "\\[").replace(']', "\\]"),
+        (None, Some(name)) => name.clone(),
+        (None, None) => describe_image(file_stream, extension),
+    };
+    if frames > 1 {
+        alt_text.push_str(&format!(" (animated, {} frames)", frames));
+    }
+
     match mode {
+        ImageProcessingMode::Base64 if extension == "svg" && cfg.inline_svg => {
+            // Markdown allows raw HTML, so the SVG can be dropped in as-is
+            Ok(inline_svg_markup(file_stream))
+        }
         ImageProcessingMode::Base64 => {
             // Encode the image data to base64
             let encoded = base64::engine::general_purpose::STANDARD.encode(file_stream);
-            let md_content = format!("![{}](data:{};base64,{})", image_name, mime_type, encoded);
-            Ok(md_content)
+            let target = format!("data:{};base64,{}", mime_type, encoded);
+            Ok(image_link(&alt_text, &target, cfg.reference_images))
         }
         ImageProcessingMode::SaveToFile => {
             // Save image to file and return markdown reference
+            let image_name = image_name(cfg, file_stream, ai_name.as_deref());
             let filename = format!("{}.{}", image_name, extension);
+
+            let target = link_target(cfg, &filename);
+
+            // Inside a conversion the bytes are returned with the result for the caller to write
+            if crate::context::defer_image(PathBuf::from(&filename), file_stream) {
+                return Ok(image_link(&alt_text, &target, cfg.reference_images));
+            }
+
             let file_path = cfg.image_path.join(&filename);
             
             // Ensure the directory exists
@@ -74,43 +152,245 @@ pub fn run_with_mode(file_stream: &[u8], mode: ImageProcessingMode) -> Result<St
             fs::write(&file_path, file_stream)
                 .map_err(|e| format!("Failed to save image file: {}", e))?;
             
-            // Return markdown reference to the saved file (just the filename for relative path)
-            let md_content = format!("![{}]({})", image_name, filename);
-            Ok(md_content)
+            Ok(image_link(&alt_text, &target, cfg.reference_images))
         }
     }
 }
 
 
-fn ai_generate_name_from_bytes(file_stream: &[u8], mime_type: &str) -> String {
-    let encoded = base64::engine::general_purpose::STANDARD.encode(file_stream);
-    ai_generate_name(encoded, mime_type)
+/// Link target for `file_name` saved under `Settings.image_path`, per `image_link_style`
+pub fn link_target(cfg: &Settings, file_name: &str) -> String {
+    let directory = match cfg.image_link_style {
+        ImageLinkStyle::FilenameOnly => None,
+        ImageLinkStyle::Absolute => Some(
+            cfg.image_path
+                .canonicalize()
+                .unwrap_or_else(|_| std::env::current_dir().unwrap_or_default().join(&cfg.image_path)),
+        ),
+        // From the output file's directory to the image directory, `../` included;
+        // without an output file the Markdown is assumed to sit next to the images
+        ImageLinkStyle::Relative => cfg
+            .output_path
+            .as_ref()
+            .filter(|output_path| !output_path.as_os_str().is_empty())
+            .and_then(|output_path| {
+                let output_dir = output_path.parent().unwrap_or(std::path::Path::new(""));
+                pathdiff::diff_paths(absolute_path(&cfg.image_path), absolute_path(output_dir))
+            }),
+    };
+
+    match directory {
+        Some(directory) if !directory.as_os_str().is_empty() => {
+            format!("{}/{}", directory.to_string_lossy().replace('\\', "/"), file_name)
+        }
+        _ => file_name.to_string(),
+    }
+}
+
+
+// Against the working directory, so relative and absolute settings can be compared
+fn absolute_path(path: &std::path::Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    }
+}
+
+
+// `![alt](target)`, or `![alt][imageN]` when reference-style links are enabled
+// and a conversion is collecting the definitions
+fn image_link(alt_text: &str, target: &str, reference_style: bool) -> String {
+    let reference = if reference_style {
+        crate::context::image_reference(target)
+    } else {
+        None
+    };
+
+    match reference {
+        Some(id) => format!("![{}][{}]", alt_text, id),
+        None => format!("![{}]({})", alt_text, target),
+    }
+}
+
+
+/// SVG is plain XML, so `infer` can't see it; sniff the head of the stream instead.
+pub fn is_svg(file_stream: &[u8]) -> bool {
+    let head = &file_stream[..file_stream.len().min(1024)];
+    String::from_utf8_lossy(head).contains("<svg")
+}
+
+
+/// Stricter `is_svg` for whole-file detection: the root element has to be `<svg>`
+/// (after any BOM, XML declaration, comments and doctype), so HTML with an inline
+/// SVG isn't mistaken for one
+pub fn is_svg_document(file_stream: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&file_stream[..file_stream.len().min(4096)]);
+    let mut rest = head.trim_start_matches('\u{feff}').trim_start();
+    loop {
+        let skipped = if rest.starts_with("<?") {
+            rest.find("?>").map(|end| end + 2)
+        } else if rest.starts_with("<!--") {
+            rest.find("-->").map(|end| end + 3)
+        } else if rest.starts_with("<!") {
+            rest.find('>').map(|end| end + 1)
+        } else {
+            return rest.starts_with("<svg");
+        };
+        match skipped {
+            Some(end) => rest = rest[end..].trim_start(),
+            None => return false,
+        }
+    }
+}
+
+
+// e.g. "PNG image 800x600"; dimensions are omitted when they can't be decoded
+fn describe_image(file_stream: &[u8], extension: &str) -> String {
+    let format = match extension {
+        "jpg" => "JPEG".to_string(),
+        other => other.to_uppercase(),
+    };
+
+    let dimensions = image::io::Reader::new(Cursor::new(file_stream))
+        .with_guessed_format()
+        .ok()
+        .and_then(|reader| reader.into_dimensions().ok());
+
+    match dimensions {
+        Some((width, height)) => format!("{} image {}x{}", format, width, height),
+        None => format!("{} image", format),
+    }
+}
+
+// Strip the XML prolog/doctype so the markup starts at the <svg> element
+fn inline_svg_markup(file_stream: &[u8]) -> String {
+    let svg = String::from_utf8_lossy(file_stream);
+    let start = svg.find("<svg").unwrap_or(0);
+    svg[start..].trim().to_string()
+}
+
+
+/// Base file name for a saved image: `Settings.image_name_template` when set,
+/// otherwise the AI description when enabled, otherwise a timestamp name
+pub fn generate_image_name(file_stream: &[u8]) -> String {
+    saved_image_name(&SETTINGS.read().unwrap(), file_stream)
+}
+
+// `generate_image_name` for a caller that already holds the settings
+pub(crate) fn saved_image_name(cfg: &Settings, file_stream: &[u8]) -> String {
+    let ai_name = cfg.is_ai_enpower.then(|| {
+        let mime_type = infer::get(file_stream)
+            .map(|kind| kind.mime_type())
+            .unwrap_or("image/png");
+        ai_generate_name_from_bytes(file_stream, mime_type)
+    });
+    image_name(cfg, file_stream, ai_name.as_deref())
+}
+
+
+// Name without extension, unique within the current conversion
+fn image_name(cfg: &Settings, file_stream: &[u8], ai_name: Option<&str>) -> String {
+    static FALLBACK_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+    let name = match cfg.image_name_template.as_deref().filter(|template| !template.is_empty()) {
+        Some(template) => {
+            let hash = format!("{:x}", Sha256::digest(file_stream));
+            let source_stem = crate::context::source_stem();
+            let tokens = NameTokens {
+                // Outside a conversion, count per process instead
+                index: crate::context::next_image_index()
+                    .unwrap_or_else(|| FALLBACK_INDEX.fetch_add(1, Ordering::Relaxed) + 1),
+                hash8: &hash[..8],
+                timestamp: chrono::Utc::now().timestamp_millis(),
+                ai: ai_name.unwrap_or("image"),
+                source_stem: source_stem.as_deref().unwrap_or("image"),
+            };
+            fill_name_template(template, &tokens)
+        }
+        None => match ai_name {
+            Some(name) => name.to_string(),
+            None => timestamp_name(file_stream),
+        },
+    };
+    crate::context::claim_image_name(name)
+}
+
+
+struct NameTokens<'a> {
+    index: usize,
+    hash8: &'a str,
+    timestamp: i64,
+    ai: &'a str,
+    source_stem: &'a str,
+}
+
+// `{index}` is zero-padded to three digits; the result is sanitized as a whole
+fn fill_name_template(template: &str, tokens: &NameTokens) -> String {
+    let name = template
+        .replace("{index}", &format!("{:03}", tokens.index))
+        .replace("{hash8}", tokens.hash8)
+        .replace("{timestamp}", &tokens.timestamp.to_string())
+        .replace("{ai}", tokens.ai)
+        .replace("{source_stem}", tokens.source_stem);
+    sanitize_file_name(&name)
+}
+
+
+/// `pic-{unix_millis}-{sequence}-{hash}`: the per-process sequence keeps images
+/// extracted within the same millisecond apart, the content hash prefix makes
+/// names from different runs distinct as well
+fn timestamp_name(file_stream: &[u8]) -> String {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+    let millis = chrono::Utc::now().timestamp_millis();
+    let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    let hash = format!("{:x}", Sha256::digest(file_stream));
+    format!("pic-{}-{}-{}", millis, sequence, &hash[..8])
 }
 
 
-fn ai_generate_name(encoded: String, mime_type: &str) -> String {
+fn ai_generate_name_from_bytes(file_stream: &[u8], mime_type: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(file_stream);
     // Try to generate name using Doubao API, fallback to timestamp if failed
     match call_doubao_api(&encoded, mime_type) {
         Ok(name) => name,
-        Err(_) => {
-            // Fallback to timestamp-based name if AI call fails
-            let timestamp = chrono::Utc::now().timestamp();
-            format!("pic-{}", timestamp)
-        }
+        Err(_) => timestamp_name(file_stream),
     }
 }
 
+const DOUBAO_API_URL: &str = "https://ark.cn-beijing.volces.com/api/v3/chat/completions";
+
 fn call_doubao_api(encoded_image: &str, mime_type: &str) -> Result<String, Box<dyn std::error::Error>> {
-    use serde_json::json;
-    
-    // Doubao API endpoint and key (you should configure these in your SETTINGS)
-    let api_url = "https://ark.cn-beijing.volces.com/api/v3/chat/completions";
-    let cfg = &*SETTINGS.read().unwrap();
-    let api_key = cfg.doubao_api_key.as_ref()
+    let api_key = SETTINGS.read().unwrap().doubao_api_key.clone()
         .ok_or("Doubao API key not configured")?;
+    let payload = doubao_payload(encoded_image, mime_type);
     
-    // Prepare the request payload using serde_json::json! macro
-    let payload = json!({
+    // Make HTTP request
+    let client = ureq::Agent::new();
+    log::debug!("Sending API request to Doubao: {}", DOUBAO_API_URL);
+    let response = client
+        .post(DOUBAO_API_URL)
+        .set("Authorization", &format!("Bearer {}", api_key))
+        .set("Content-Type", "application/json")
+        .set("Accept", "application/json")
+        .send_json(&payload)?;
+
+    if response.status() != 200 {
+        log::warn!("Doubao API request failed with status: {}", response.status());
+        return Err(format!("API request failed with status: {}", response.status()).into());
+    }
+    log::debug!("Doubao API request succeeded with status: {}", response.status());
+    
+    // Parse response
+    let response_json: serde_json::Value = response.into_json()?;
+    Ok(generated_name(&response_json))
+}
+
+fn doubao_payload(encoded_image: &str, mime_type: &str) -> serde_json::Value {
+    use serde_json::json;
+
+    json!({
         "model": "doubao-1-5-thinking-vision-pro-250428",
         "messages": [
             {
@@ -131,48 +411,158 @@ fn call_doubao_api(encoded_image: &str, mime_type: &str) -> Result<String, Box<d
         ],
         "max_tokens": 50,
         "temperature": 0.7
-    });
-    
-    // Make HTTP request
-    let client = ureq::Agent::new();
-    println!("Sending API request to Doubao: {}", api_url);
-    let response = client
-        .post(api_url)
-        .set("Authorization", &format!("Bearer {}", api_key))
-        .set("Content-Type", "application/json")
-        .set("Accept", "application/json")
-        .send_json(&payload)?;
+    })
+}
 
-    println!("API request sent to Doubao: {}", api_url);
-    // print response status and headers for debugging
-    if response.status() != 200 {
-        println!("API request failed with status: {}", response.status());
-        return Err(format!("API request failed with status: {}", response.status()).into());
-    } else {
-        println!("API request succeeded with status: {}", response.status());
-    }
-    
-    // Parse response
-    let response_json: serde_json::Value = response.into_json()?;
-    
-    let generated_name = response_json
+fn generated_name(response_json: &serde_json::Value) -> String {
+    let name = response_json
         .get("choices")
         .and_then(|choices| choices.get(0))
         .and_then(|choice| choice.get("message"))
         .and_then(|message| message.get("content"))
         .and_then(|content| content.as_str())
         .unwrap_or("generated-image")
-        .trim()
-        .replace(" ", "-")
-        .replace("/", "-")
-        .replace("\\", "-")
-        .replace(":", "-")
-        .replace("*", "-")
-        .replace("?", "-")
-        .replace("\"", "-")
-        .replace("<", "-")
-        .replace(">", "-")
-        .replace("|", "-");
-    
-    Ok(generated_name)
+        .trim();
+    sanitize_file_name(name)
+}
+
+
+/// Async counterpart of the AI image naming: the Doubao request goes through
+/// `reqwest`'s async client, so it never blocks a runtime worker. Falls back to a
+/// timestamp name like the blocking path.
+#[cfg(feature = "async")]
+pub async fn ai_generate_name_async(file_stream: &[u8], mime_type: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(file_stream);
+    match call_doubao_api_async(&encoded, mime_type).await {
+        Ok(name) => name,
+        Err(_) => timestamp_name(file_stream),
+    }
+}
+
+#[cfg(feature = "async")]
+async fn call_doubao_api_async(encoded_image: &str, mime_type: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let api_key = SETTINGS.read().unwrap().doubao_api_key.clone()
+        .ok_or("Doubao API key not configured")?;
+
+    let response = reqwest::Client::new()
+        .post(DOUBAO_API_URL)
+        .bearer_auth(api_key)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .body(doubao_payload(encoded_image, mime_type).to_string())
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("API request failed with status: {}", response.status()).into());
+    }
+
+    let response_json: serde_json::Value = serde_json::from_str(&response.text().await?)?;
+    Ok(generated_name(&response_json))
+}
+
+
+/// Make a (possibly model- or archive-supplied) name safe to join onto a directory:
+/// only letters, digits, `-`, `_` and inner dots survive, so no separators or `..`
+pub fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '-' })
+        .collect();
+    let sanitized = sanitized.trim_matches('.');
+    if sanitized.is_empty() {
+        "image".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_settings;
+
+    #[test]
+    fn test_save_to_file_names_are_unique() {
+        let image_dir = std::env::temp_dir().join(format!("markitup_image2md_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&image_dir);
+        let mut settings = Settings::builtin();
+        settings.image_path = image_dir.clone();
+        settings.is_ai_enpower = false;
+
+        // Three different images converted back to back, well within one second
+        let png_header = b"\x89PNG\r\n\x1a\n".to_vec();
+        with_settings(settings, || {
+            for i in 0..3u8 {
+                let mut image = png_header.clone();
+                image.push(i);
+                run_with_mode(&image, ImageProcessingMode::SaveToFile).unwrap();
+            }
+        });
+
+        let saved = fs::read_dir(&image_dir).unwrap().count();
+        let _ = fs::remove_dir_all(&image_dir);
+        assert_eq!(saved, 3);
+    }
+
+    #[test]
+    fn test_document_alt_text_is_preferred() {
+        let mut settings = Settings::builtin();
+        settings.is_ai_enpower = false;
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+        with_settings(settings, || {
+            let markdown = run_with_alt(&png, ImageProcessingMode::Base64, Some(" Sales\nby [region] ")).unwrap();
+            assert!(markdown.starts_with("![Sales by \\[region\\]](data:image/png;base64,"), "{}", markdown);
+            let markdown = run_with_alt(&png, ImageProcessingMode::Base64, Some("  ")).unwrap();
+            assert!(markdown.starts_with("![PNG image]("), "{}", markdown);
+        });
+    }
+
+    #[test]
+    fn test_link_target_styles() {
+        let mut cfg = crate::config::get_settings();
+        cfg.image_path = PathBuf::from("out/images");
+        cfg.output_path = Some(PathBuf::from("out/doc.md"));
+
+        cfg.image_link_style = ImageLinkStyle::Relative;
+        assert_eq!(link_target(&cfg, "pic.png"), "images/pic.png");
+        // Sibling directories: the link has to climb out of the output directory
+        cfg.output_path = Some(PathBuf::from("out/docs/doc.md"));
+        assert_eq!(link_target(&cfg, "pic.png"), "../images/pic.png");
+        cfg.output_path = Some(absolute_path(std::path::Path::new("site/index.md")));
+        assert_eq!(link_target(&cfg, "pic.png"), "../out/images/pic.png");
+        cfg.output_path = Some(PathBuf::from("out/doc.md"));
+        cfg.image_link_style = ImageLinkStyle::FilenameOnly;
+        assert_eq!(link_target(&cfg, "pic.png"), "pic.png");
+        cfg.image_link_style = ImageLinkStyle::Absolute;
+        let absolute = link_target(&cfg, "pic.png");
+        assert!(std::path::Path::new(&absolute).is_absolute() && absolute.ends_with("out/images/pic.png"));
+    }
+
+    #[test]
+    fn test_is_svg_document() {
+        let svg = b"\xef\xbb\xbf<?xml version=\"1.0\"?>\n<!-- logo -->\n<!DOCTYPE svg>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>";
+        assert!(is_svg_document(svg));
+        assert!(!is_svg_document(b"<html><body><svg></svg></body></html>"));
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "-..-etc-passwd");
+        assert_eq!(sanitize_file_name(".."), "image");
+        assert_eq!(sanitize_file_name("red apple.png"), "red-apple.png");
+    }
+
+    #[test]
+    fn test_fill_name_template() {
+        let tokens = NameTokens {
+            index: 1,
+            hash8: "0123abcd",
+            timestamp: 1700000000000,
+            ai: "bar-chart",
+            source_stem: "Q3 report",
+        };
+        assert_eq!(fill_name_template("report-img-{index}", &tokens), "report-img-001");
+        assert_eq!(fill_name_template("{source_stem}_{ai}_{hash8}", &tokens), "Q3-report_bar-chart_0123abcd");
+        assert_eq!(fill_name_template("{timestamp}", &tokens), "1700000000000");
+    }
 }
@@ -0,0 +1,184 @@
+// JSON -> arrays of objects as tables, everything else as nested bullet lists
+
+use crate::config::SETTINGS;
+use crate::table::{render_table, TableStyle};
+use serde_json::{Map, Value};
+
+// Deeper values are written inline as compact JSON
+const MAX_DEPTH: usize = 32;
+
+struct Style {
+    bullet: char,
+    table_style: TableStyle,
+    empty_cell: String,
+}
+
+pub fn run(bytes: &[u8]) -> Result<String, String> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let value: Value = serde_json::from_slice(bytes).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let style = {
+        let cfg = SETTINGS.read().unwrap();
+        Style {
+            bullet: cfg.bullet_char,
+            table_style: cfg.effective_table_style(),
+            empty_cell: cfg.empty_cell_placeholder.clone(),
+        }
+    };
+
+    if let Some(records) = as_records(&value) {
+        return Ok(render_records(records, &style));
+    }
+    let markdown = match &value {
+        // `{"data": [...], "meta": {...}}`: one section per table-shaped field
+        Value::Object(map) => render_document(map, &style),
+        Value::Array(items) => render_array(items, 0, &style),
+        scalar => format!("{}\n", scalar_text(scalar)),
+    };
+    Ok(markdown)
+}
+
+fn render_document(map: &Map<String, Value>, style: &Style) -> String {
+    let mut markdown = String::new();
+    let mut rest = Map::new();
+    let mut sections = Vec::new();
+
+    for (key, value) in map {
+        match as_records(value) {
+            Some(records) => sections.push(format!("## {}\n\n{}", key, render_records(records, style))),
+            None => {
+                rest.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    if !rest.is_empty() {
+        markdown.push_str(&render_object(&rest, 0, style));
+    }
+    for section in sections {
+        if !markdown.is_empty() {
+            markdown.push('\n');
+        }
+        markdown.push_str(&section);
+    }
+    markdown
+}
+
+// A non-empty array whose items are all objects
+fn as_records(value: &Value) -> Option<Vec<&Map<String, Value>>> {
+    let items = value.as_array().filter(|items| !items.is_empty())?;
+    items.iter().map(Value::as_object).collect()
+}
+
+// Columns are every key in first-seen order; missing keys are blank cells
+fn render_records(records: Vec<&Map<String, Value>>, style: &Style) -> String {
+    let mut columns: Vec<&String> = Vec::new();
+    for record in &records {
+        for key in record.keys() {
+            if !columns.contains(&key) {
+                columns.push(key);
+            }
+        }
+    }
+
+    let mut rows = vec![columns.iter().map(|key| key.to_string()).collect::<Vec<_>>()];
+    for record in &records {
+        rows.push(
+            columns
+                .iter()
+                .map(|key| record.get(key.as_str()).map(cell_text).unwrap_or_default())
+                .collect(),
+        );
+    }
+    render_table(&rows, style.table_style, &style.empty_cell)
+}
+
+fn render_object(map: &Map<String, Value>, depth: usize, style: &Style) -> String {
+    let indent = "  ".repeat(depth);
+    let mut markdown = String::new();
+    for (key, value) in map {
+        match value {
+            Value::Object(_) | Value::Array(_) if depth + 1 >= MAX_DEPTH => {
+                markdown.push_str(&format!("{}{} **{}**: `{}`\n", indent, style.bullet, key, value));
+            }
+            Value::Object(child) if !child.is_empty() => {
+                markdown.push_str(&format!("{}{} **{}**:\n", indent, style.bullet, key));
+                markdown.push_str(&render_object(child, depth + 1, style));
+            }
+            Value::Array(items) if !items.is_empty() => {
+                markdown.push_str(&format!("{}{} **{}**:\n", indent, style.bullet, key));
+                markdown.push_str(&render_array(items, depth + 1, style));
+            }
+            _ => markdown.push_str(&format!("{}{} **{}**: {}\n", indent, style.bullet, key, scalar_text(value))),
+        }
+    }
+    markdown
+}
+
+fn render_array(items: &[Value], depth: usize, style: &Style) -> String {
+    let indent = "  ".repeat(depth);
+    let mut markdown = String::new();
+    for (index, item) in items.iter().enumerate() {
+        match item {
+            Value::Object(_) | Value::Array(_) if depth + 1 >= MAX_DEPTH => {
+                markdown.push_str(&format!("{}{} `{}`\n", indent, style.bullet, item));
+            }
+            Value::Object(child) if !child.is_empty() => {
+                markdown.push_str(&format!("{}{} **[{}]**\n", indent, style.bullet, index));
+                markdown.push_str(&render_object(child, depth + 1, style));
+            }
+            Value::Array(child) if !child.is_empty() => {
+                markdown.push_str(&format!("{}{} **[{}]**\n", indent, style.bullet, index));
+                markdown.push_str(&render_array(child, depth + 1, style));
+            }
+            _ => markdown.push_str(&format!("{}{} {}\n", indent, style.bullet, scalar_text(item))),
+        }
+    }
+    markdown
+}
+
+// Strings unquoted and on one line; `{}`/`[]`/null as written
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        other => other.to_string(),
+    }
+}
+
+// Nested values stay compact JSON inside a cell; null is a blank cell
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_array_of_objects_becomes_table() {
+        let markdown = run(br#"[{"name": "Ada", "age": 36}, {"name": "Alan", "city": "London"}]"#).unwrap();
+        assert!(markdown.starts_with("| name | age | city |\n"), "{}", markdown);
+        assert!(markdown.contains("| Alan |"), "{}", markdown);
+    }
+
+    #[test]
+    fn test_nested_object_becomes_nested_list() {
+        let markdown = run(br#"{"user": {"name": "Ada", "tags": ["a", "b"]}, "ok": true}"#).unwrap();
+        let bullet = SETTINGS.read().unwrap().bullet_char;
+        let expected = "- **user**:\n  - **name**: Ada\n  - **tags**:\n    - a\n    - b\n- **ok**: true\n"
+            .replace('-', &bullet.to_string());
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn test_depth_limit_inlines_json() {
+        let json = format!("{}1{}", "[".repeat(MAX_DEPTH + 5), "]".repeat(MAX_DEPTH + 5));
+        let markdown = run(json.as_bytes()).unwrap();
+        assert_eq!(markdown.lines().count(), MAX_DEPTH);
+        assert!(markdown.trim_end().ends_with("`[[[[[1]]]]]`"), "{}", markdown);
+    }
+}
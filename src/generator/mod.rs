@@ -1,6 +1,107 @@
+//! One `<format>2md` module per input format, each turning raw bytes into Markdown.
+//! Intermediate format conversions they rely on live in `converter`.
+
 pub mod wav2md;
 pub mod docx2md;
 pub mod image2md;
 pub mod pptx2md;
 pub mod csv2md;
-pub mod html2md;
\ No newline at end of file
+pub mod json2md;
+pub mod xml2md;
+pub mod zip2md;
+pub mod html2md;
+pub mod odt2md;
+pub mod vcard2md;
+pub mod ical2md;
+pub mod eml2md;
+pub mod text2md;
+
+use crate::config::SETTINGS;
+use crate::context;
+use crate::opc;
+use image2md::ImageProcessingMode;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// `# title` opening a DOCX/PPTX document: `Settings.document_title` when set,
+/// else the package's own title (core properties) unless `synthetic_title` is off,
+/// else nothing
+pub(crate) fn title_heading(file_stream: &[u8]) -> String {
+    let (configured, synthetic_title) = {
+        let cfg = SETTINGS.read().unwrap();
+        (cfg.document_title.clone(), cfg.synthetic_title)
+    };
+    configured
+        .or_else(|| {
+            crate::opc::core_properties(file_stream)
+                .and_then(|properties| properties.title)
+                .filter(|_| synthetic_title)
+        })
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .map(|title| format!("# {}\n\n", title))
+        .unwrap_or_default()
+}
+
+/// Add `## Attachments` for the files a DOCX/PPTX/XLSX package embeds under `dir`
+/// (OLE objects, attached PDFs, workbooks...). In SaveToFile mode each file is saved
+/// with the images and linked as `[attachment: name](path)`; otherwise it is only named.
+pub(crate) fn append_attachments(markdown: &mut String, file_stream: &[u8], dir: &str) {
+    let parts = ZipArchive::new(Cursor::new(file_stream))
+        .map_err(|e| format!("Failed to open archive: {}", e))
+        .and_then(|mut archive| opc::read_parts_under(&mut archive, dir));
+    let parts = match parts {
+        Ok(parts) if !parts.is_empty() => parts,
+        Ok(_) => return,
+        Err(e) => {
+            context::warn(format!("Embedded files could not be read: {}", e));
+            return;
+        }
+    };
+
+    let cfg = SETTINGS.read().unwrap();
+    let save = cfg.image_processing_mode() == ImageProcessingMode::SaveToFile && !cfg.skip_images;
+    let mut items = Vec::new();
+    for (part_name, data) in parts {
+        let name = part_name.rsplit('/').next().unwrap_or(&part_name).to_string();
+        if !save {
+            items.push(format!("{} attachment: {}", cfg.bullet_char, name));
+            continue;
+        }
+        match save_attachment(&cfg, &name, &data) {
+            Ok(target) => items.push(format!("{} [attachment: {}]({})", cfg.bullet_char, name, target)),
+            Err(e) => {
+                context::warn(e);
+                items.push(format!("{} attachment: {}", cfg.bullet_char, name));
+            }
+        }
+    }
+
+    let trimmed = markdown.trim_end().len();
+    markdown.truncate(trimmed);
+    if !markdown.is_empty() {
+        markdown.push_str("\n\n");
+    }
+    markdown.push_str("## Attachments\n\n");
+    markdown.push_str(&items.join("\n"));
+    markdown.push('\n');
+}
+
+// Named like a saved image (clashes get `-N`); returns the link target
+fn save_attachment(cfg: &crate::config::Settings, name: &str, data: &[u8]) -> Result<String, String> {
+    let path = Path::new(name);
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let file_name = format!("{}{}", context::claim_image_name(stem), extension);
+
+    // Inside a conversion the bytes go back with the result, like images
+    if !context::defer_image(PathBuf::from(&file_name), data) {
+        let file_path = cfg.image_path.join(&file_name);
+        std::fs::create_dir_all(&cfg.image_path)
+            .and_then(|_| std::fs::write(&file_path, data))
+            .map_err(|e| format!("Failed to save attachment {}: {}", file_path.display(), e))?;
+    }
+    let target = image2md::link_target(cfg, &file_name);
+    Ok(if target.contains([' ', '(', ')']) { format!("<{}>", target) } else { target })
+}
@@ -0,0 +1,173 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::io::{Cursor, Read, Seek};
+use zip::ZipArchive;
+use crate::generator::image2md;
+use crate::config::SETTINGS;
+use crate::opc;
+
+// Paragraph or heading currently being collected
+struct Block {
+    prefix: String,
+    text: String,
+    in_list: bool,
+}
+
+pub fn run(file_stream: &[u8]) -> Result<String, String> {
+    let mut archive = ZipArchive::new(Cursor::new(file_stream))
+        .map_err(|e| format!("Failed to open ODT archive: {}", e))?;
+
+    let content = opc::read_part_to_string(&mut archive, "content.xml")?
+        .ok_or_else(|| "Missing content.xml in ODT archive".to_string())?;
+
+    parse_content(&content, &mut archive)
+}
+
+fn parse_content<R: Read + Seek>(
+    xml_content: &str,
+    archive: &mut ZipArchive<R>,
+) -> Result<String, String> {
+    let mut reader = Reader::from_str(xml_content);
+    let mut markdown = String::new();
+    let mut buf = Vec::new();
+    let mut skip_buf = Vec::new();
+
+    let mut block: Option<Block> = None;
+    let mut pending_images = Vec::new();
+    let mut list_depth = 0usize;
+    // The first paragraph of a list item carries the bullet, later ones are indented
+    let mut item_started = false;
+    let limit = crate::output_limit();
+    let bullet = format!("{} ", SETTINGS.read().unwrap().bullet_char);
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => match element.name().as_ref() {
+                b"text:h" if block.is_none() => {
+                    let level = opc::attribute_value(&element, b"text:outline-level")?
+                        .and_then(|level| level.parse::<usize>().ok())
+                        .unwrap_or(1)
+                        .clamp(1, 6);
+                    block = Some(Block {
+                        prefix: format!("{} ", "#".repeat(level)),
+                        text: String::new(),
+                        in_list: false,
+                    });
+                }
+                b"text:p" if block.is_none() => {
+                    let prefix = if list_depth > 0 {
+                        let marker = if item_started { bullet.as_str() } else { "  " };
+                        item_started = false;
+                        format!("{}{}", "  ".repeat(list_depth - 1), marker)
+                    } else {
+                        String::new()
+                    };
+                    block = Some(Block {
+                        prefix,
+                        text: String::new(),
+                        in_list: list_depth > 0,
+                    });
+                }
+                b"text:list" => list_depth += 1,
+                b"text:list-item" => item_started = true,
+                // Footnotes, comments and deleted revisions aren't part of the body text
+                b"text:note" | b"office:annotation" | b"text:tracked-changes" => {
+                    reader
+                        .read_to_end_into(element.name(), &mut skip_buf)
+                        .map_err(|e| format!("Error parsing ODT content: {}", e))?;
+                    skip_buf.clear();
+                }
+                b"draw:image" => {
+                    if let Some(image_md) = read_image(&element, archive)? {
+                        pending_images.push(image_md);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(element)) => match element.name().as_ref() {
+                b"text:s" => {
+                    if let Some(block) = block.as_mut() {
+                        let count = opc::attribute_value(&element, b"text:c")?
+                            .and_then(|count| count.parse::<usize>().ok())
+                            .unwrap_or(1);
+                        block.text.push_str(&" ".repeat(count));
+                    }
+                }
+                b"text:tab" => {
+                    if let Some(block) = block.as_mut() {
+                        block.text.push(' ');
+                    }
+                }
+                b"text:line-break" => {
+                    if let Some(block) = block.as_mut() {
+                        block.text.push_str("  \n");
+                    }
+                }
+                b"draw:image" => {
+                    if let Some(image_md) = read_image(&element, archive)? {
+                        pending_images.push(image_md);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(text)) => {
+                if let Some(block) = block.as_mut() {
+                    block.text.push_str(&text.unescape().unwrap_or_default());
+                }
+            }
+            Ok(Event::End(element)) => match element.name().as_ref() {
+                b"text:h" | b"text:p" => {
+                    if let Some(block) = block.take() {
+                        let text = block.text.trim();
+                        if !text.is_empty() {
+                            markdown.push_str(&block.prefix);
+                            markdown.push_str(text);
+                            markdown.push_str(if block.in_list { "\n" } else { "\n\n" });
+                        }
+                    }
+                    for image_md in pending_images.drain(..) {
+                        markdown.push_str(&image_md);
+                        markdown.push_str("\n\n");
+                    }
+
+                    if let Some(limit) = limit
+                        && crate::truncate_output(&mut markdown, limit)
+                    {
+                        break;
+                    }
+                }
+                b"text:list" => {
+                    list_depth = list_depth.saturating_sub(1);
+                    if list_depth == 0 {
+                        markdown.push('\n');
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Error parsing ODT content: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(markdown)
+}
+
+// Embedded pictures live under `Pictures/` and are referenced by `xlink:href`
+fn read_image<R: Read + Seek>(
+    element: &BytesStart,
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<String>, String> {
+    if SETTINGS.read().unwrap().skip_images {
+        return Ok(None);
+    }
+    let Some(href) = opc::attribute_value(element, b"xlink:href")? else {
+        return Ok(None);
+    };
+    let Some(data) = opc::read_part_bytes(archive, href.trim_start_matches("./"))? else {
+        return Ok(None);
+    };
+
+    image2md::run(&data).map(Some)
+}
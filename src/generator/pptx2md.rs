@@ -1,19 +1,49 @@
 use quick_xml::events::Event;
 use quick_xml::Reader;
-use std::io::{Cursor, Read};
+use std::io::Cursor;
 use zip::ZipArchive;
-use crate::generator::image2md::{self, ImageProcessingMode};
+use crate::generator::image2md;
 use crate::config::SETTINGS;
+use crate::opc::{self, Relationship};
+use crate::markdown::MarkdownBuilder;
 use std::collections::HashMap;
-use std::path::Path;
 
 #[derive(Debug, Clone)]
 struct TableData {
     rows: Vec<Vec<String>>,
 }
 
+/// Bullet style declared in a paragraph's `a:pPr`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BulletKind {
+    Unspecified,
+    Numbered,
+    Char,
+    None,
+}
+
+/// Target of an `a:hlinkClick`
+#[derive(Debug, Clone, PartialEq)]
+enum Hyperlink {
+    /// External URL (web, mailto:, file)
+    Url(String),
+    /// Jump inside the deck, kept as a label such as "Slide 3" or "next slide"
+    Slide(String),
+}
+
+impl Hyperlink {
+    fn apply(&self, text: &str) -> String {
+        match self {
+            Hyperlink::Url(url) => format!("[{}]({})", text, url),
+            Hyperlink::Slide(label) => format!("{} (→ {})", text, label),
+        }
+    }
+}
+
 pub fn run(file_stream: &[u8]) -> Result<String, String> {
-    run_with_images(file_stream)
+    let mut markdown = run_with_images(file_stream)?;
+    super::append_attachments(&mut markdown, file_stream, "ppt/embeddings/");
+    Ok(markdown)
 }
 
 fn run_with_images(file_stream: &[u8]) -> Result<String, String> {
@@ -21,88 +51,217 @@ fn run_with_images(file_stream: &[u8]) -> Result<String, String> {
     let mut archive = ZipArchive::new(cursor)
         .map_err(|e| format!("Failed to open PPTX archive: {}", e))?;
 
-    // First, extract all images from the archive
-    let mut images = HashMap::new();
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| format!("Failed to access file in ZIP archive: {}", e))?;
-        
-        if file.name().starts_with("ppt/media/") {
-            let mut image_data = Vec::new();
-            file.read_to_end(&mut image_data)
-                .map_err(|e| format!("Failed to read image data: {}", e))?;
-            
-            let filename = file.name().to_string();
-            images.insert(filename, image_data);
-        }
-    }
+    // First, extract all images from the archive (none at all when images are skipped)
+    let images: HashMap<String, Vec<u8>> = if SETTINGS.read().unwrap().skip_images {
+        HashMap::new()
+    } else {
+        opc::read_parts_under(&mut archive, "ppt/media/")?
+            .into_iter()
+            .collect()
+    };
 
     // Reset archive for slide processing
     let cursor = Cursor::new(file_stream);
     let mut archive = ZipArchive::new(cursor)
         .map_err(|e| format!("Failed to open PPTX archive: {}", e))?;
 
-    let mut markdown = String::new();
-    markdown.push_str("# PowerPoint Presentation\n\n");
+    let mut md = MarkdownBuilder::new();
+    md.raw(&super::title_heading(file_stream));
 
     let mut slide_num = 1;
+    let limit = crate::output_limit();
+    let include_hidden = SETTINGS.read().unwrap().include_hidden_slides;
 
     // Process all slides in the archive
     for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| format!("Failed to access file in ZIP archive: {}", e))?;
-        
-        if file.name().starts_with("ppt/slides/") && file.name().ends_with(".xml") {
-            markdown.push_str(&format!("## Slide {}\n\n", slide_num));
-            slide_num += 1;
-            
-            let mut content = String::new();
-            file.read_to_string(&mut content)
-                .map_err(|e| format!("Failed to read slide content: {}", e))?;
-
-            let slide_markdown = parse_slide_content(&content, &images)?;
-            markdown.push_str(&slide_markdown);
-            markdown.push_str("\n\n---\n\n");
+        let (slide_name, content) = {
+            // An unreadable entry may or may not be a slide; either way the others still convert
+            let mut file = match archive.by_index(i) {
+                Ok(file) => file,
+                Err(e) => {
+                    crate::context::part_failed(format!("ZIP entry {} could not be read: {}", i, e));
+                    continue;
+                }
+            };
+
+            if !(file.name().starts_with("ppt/slides/") && file.name().ends_with(".xml")) {
+                continue;
+            }
+
+            (file.name().to_string(), opc::read_entry(&mut file))
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(e) => {
+                let message = format!("Slide {} could not be read: {}", slide_num, e);
+                md.heading(2, &format!("Slide {}", slide_num)).paragraph(&format!("> [{}]", message)).rule();
+                crate::context::part_failed(message);
+                slide_num += 1;
+                continue;
+            }
+        };
+
+        // Skipped hidden slides don't take a number
+        let hidden = is_hidden_slide(&content);
+        if hidden && !include_hidden {
+            continue;
+        }
+        let suffix = if hidden { " (hidden)" } else { "" };
+        md.heading(2, &format!("Slide {}{}", slide_num, suffix));
+
+        // A broken slide is noted in place; the rest of the deck is still converted
+        let slide_markdown = String::from_utf8(content)
+            .map_err(|e| format!("Failed to read slide content: {}", e))
+            .and_then(|content| {
+                let rels = opc::read_rels(&mut archive, &slide_name)?;
+                parse_slide_content(&content, &slide_name, &images, &rels)
+            });
+        match slide_markdown {
+            Ok(slide_markdown) => {
+                md.raw(&slide_markdown);
+            }
+            Err(e) => {
+                let message = format!("Slide {} could not be parsed: {}", slide_num, e);
+                md.paragraph(&format!("> [{}]", message));
+                crate::context::part_failed(message);
+            }
+        }
+        md.rule();
+        slide_num += 1;
+
+        if limit.is_some_and(|limit| md.len() > limit) {
+            break;
         }
     }
 
+    let mut markdown = md.finish();
+    if let Some(limit) = limit {
+        crate::truncate_output(&mut markdown, limit);
+    }
     Ok(markdown)
 }
 
+// `show="0"` on the slide's root `p:sld` (PowerPoint writes it there, not in presentation.xml)
+fn is_hidden_slide(xml_content: &[u8]) -> bool {
+    let mut reader = Reader::from_reader(xml_content);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) | Ok(Event::Empty(element)) => {
+                return element.name().as_ref() == b"p:sld"
+                    && opc::attribute_value(&element, b"show")
+                        .ok()
+                        .flatten()
+                        .is_some_and(|show| show == "0" || show == "false");
+            }
+            Ok(Event::Eof) | Err(_) => return false,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+// A picture on the slide, waiting to be emitted in visual order
+struct PlacedImage {
+    // (y, x) of the owning shape's `a:off`; shapes without one sort last
+    position: (i64, i64),
+    part_name: String,
+    link: Option<Hyperlink>,
+    // the picture's own alt text (p:cNvPr descr, else title)
+    alt: Option<String>,
+}
+
 fn parse_slide_content(
     xml_content: &str, 
-    images: &HashMap<String, Vec<u8>>
+    slide_name: &str,
+    images: &HashMap<String, Vec<u8>>,
+    rels: &HashMap<String, Relationship>,
 ) -> Result<String, String> {
     let mut reader = Reader::from_str(xml_content);
-    let mut markdown = String::new();
+    let mut md = MarkdownBuilder::new();
     let mut buf = Vec::new();
+    // Click action on the whole shape (p:cNvPr/a:hlinkClick); run-level links
+    // are handled inside extract_text_body
+    let mut shape_link: Option<Hyperlink> = None;
+
+    // Only pictures referenced from this slide's own shape tree are emitted;
+    // layout/master graphics live in other parts and the slide background is decoration
+    let mut in_background = false;
+    let mut shape_offset: Option<(i64, i64)> = None;
+    let mut shape_images: Vec<String> = Vec::new();
+    let mut shape_alt: Option<String> = None;
+    let mut placed_images: Vec<PlacedImage> = Vec::new();
 
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(element)) => {
                 match element.name().as_ref() {
                     b"p:txBody" => {
-                        let text_content = extract_text_body(&mut reader)?;
-                        if !text_content.trim().is_empty() {
-                            markdown.push_str(&text_content);
-                            markdown.push_str("\n\n");
+                        let has_text = extract_text_body(&mut reader, rels, &mut md)?;
+                        if let Some(link) = shape_link.as_ref().filter(|_| has_text) {
+                            md.paragraph(&link.apply("Link"));
                         }
                     }
                     b"a:tbl" => {
-                        let table_content = extract_table(&mut reader)?;
-                        markdown.push_str(&table_content);
-                        markdown.push_str("\n");
+                        let rows = extract_table(&mut reader)?;
+                        if let Some((header, body)) = rows.split_first() {
+                            md.table(header, body);
+                        }
+                    }
+                    b"p:bg" => in_background = true,
+                    b"p:sp" | b"p:pic" => {
+                        shape_offset = None;
+                        shape_images.clear();
+                        shape_alt = None;
+                    }
+                    b"p:cNvPr" => shape_alt = alt_text(&element)?,
+                    b"a:blip" if !in_background => {
+                        shape_images.extend(resolve_image_part(&element, slide_name, rels)?);
                     }
-                    b"a:blip" => {
-                        if let Some(image_md) = process_image_element(&element, images)? {
-                            markdown.push_str(&image_md);
-                            markdown.push_str("\n\n");
+                    b"a:hlinkClick" => shape_link = resolve_hyperlink(&element, rels)?,
+                    _ => {}
+                }
+            }
+            Ok(Event::Empty(element)) => {
+                match element.name().as_ref() {
+                    b"a:hlinkClick" => shape_link = resolve_hyperlink(&element, rels)?,
+                    b"p:cNvPr" => shape_alt = alt_text(&element)?,
+                    b"a:blip" if !in_background => {
+                        shape_images.extend(resolve_image_part(&element, slide_name, rels)?);
+                    }
+                    b"a:off" if shape_offset.is_none() => {
+                        let coordinate = |key: &[u8]| -> Result<i64, String> {
+                            Ok(opc::attribute_value(&element, key)?
+                                .and_then(|value| value.parse().ok())
+                                .unwrap_or(0))
+                        };
+                        shape_offset = Some((coordinate(b"y")?, coordinate(b"x")?));
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::End(element)) => {
+                match element.name().as_ref() {
+                    b"p:bg" => in_background = false,
+                    b"p:sp" | b"p:pic" => {
+                        for part_name in shape_images.drain(..) {
+                            placed_images.push(PlacedImage {
+                                position: shape_offset.unwrap_or((i64::MAX, i64::MAX)),
+                                part_name,
+                                link: shape_link.clone(),
+                                alt: shape_alt.clone(),
+                            });
                         }
+                        shape_offset = None;
                     }
                     _ => {}
                 }
+                if matches!(
+                    element.name().as_ref(),
+                    b"p:sp" | b"p:pic" | b"p:graphicFrame" | b"p:cxnSp" | b"p:grpSp"
+                ) {
+                    shape_link = None;
+                }
             }
             Ok(Event::Eof) => break,
             Err(e) => return Err(format!("Error parsing slide XML: {}", e)),
@@ -111,173 +270,137 @@ fn parse_slide_content(
         buf.clear();
     }
 
-    Ok(markdown)
+    // Top-to-bottom, then left-to-right; a picture reused on the slide appears once
+    placed_images.sort_by_key(|image| image.position);
+    let mut emitted = std::collections::HashSet::new();
+    for image in placed_images {
+        if !emitted.insert(image.part_name.clone()) {
+            continue;
+        }
+        if let Some(image_md) = process_image_part(&image.part_name, image.alt.as_deref(), images)? {
+            // A linked picture becomes a linked image
+            let image_md = match &image.link {
+                Some(link) => link.apply(image_md.trim()),
+                None => image_md,
+            };
+            md.raw(&image_md);
+        }
+    }
+
+    Ok(md.finish())
 }
 
-fn process_image_element(
+// Media part an `a:blip r:embed` points at, e.g. `ppt/media/image1.png`
+fn resolve_image_part(
     element: &quick_xml::events::BytesStart,
-    images: &HashMap<String, Vec<u8>>
+    slide_name: &str,
+    rels: &HashMap<String, Relationship>,
 ) -> Result<Option<String>, String> {
-    let cfg = &*SETTINGS.read().unwrap();
-    
-    // Determine processing mode based on configuration
-    let mode = if cfg.image_path.as_os_str().is_empty() {
-        ImageProcessingMode::Base64
-    } else {
-        ImageProcessingMode::SaveToFile
-    };
-    
-    // Extract r:embed attribute to find the image
-    for attr_result in element.attributes() {
-        let attr = attr_result.map_err(|e| format!("Error reading attribute: {}", e))?;
-        if attr.key.as_ref() == b"r:embed" {
-            let embed_id = String::from_utf8_lossy(&attr.value);
-            
-            // Try to find matching image by filename patterns
-            for (filename, image_data) in images {
-                // Look for images that might match this embed ID or just process all images
-                if filename.contains(&*embed_id) || 
-                   filename.ends_with(".png") || 
-                   filename.ends_with(".jpg") || 
-                   filename.ends_with(".jpeg") ||
-                   filename.ends_with(".gif") ||
-                   filename.ends_with(".webp") {
-                    
-                    // Use the image2md module to process the image with proper mode
-                    let image_md = image2md::run_with_mode(image_data, mode)?;
-                    
-                    // Handle relative paths if needed
-                    let final_md = if !cfg.image_path.as_os_str().is_empty() {
-                        adjust_image_path_in_markdown(image_md)?
-                    } else {
-                        image_md
-                    };
-                    
-                    return Ok(Some(final_md));
-                }
-            }
-            
-            // If no matching image found, return a placeholder
-            return Ok(Some(format!("![Image not found]({})", embed_id)));
+    let part = opc::attribute_value(element, b"r:embed")?
+        .and_then(|id| rels.get(&id))
+        .filter(|rel| !rel.external)
+        .map(|rel| opc::resolve_target(slide_name, &rel.target));
+    Ok(part)
+}
+
+// `descr`, else `title`, of a shape's p:cNvPr
+fn alt_text(element: &quick_xml::events::BytesStart) -> Result<Option<String>, String> {
+    for key in [b"descr".as_slice(), b"title"] {
+        if let Some(text) = opc::attribute_value(element, key)?.filter(|text| !text.trim().is_empty()) {
+            return Ok(Some(text));
         }
     }
     Ok(None)
 }
 
-fn adjust_image_path_in_markdown(markdown: String) -> Result<String, String> {
+fn process_image_part(
+    part_name: &str,
+    alt: Option<&str>,
+    images: &HashMap<String, Vec<u8>>
+) -> Result<Option<String>, String> {
     let cfg = &*SETTINGS.read().unwrap();
     
-    // If we have an output path and it's not empty, try to make image paths relative
-    if let Some(output_path) = &cfg.output_path {
-        if !output_path.as_os_str().is_empty() {
-            // Calculate relative path from output directory to image directory
-            let output_dir = output_path.parent().unwrap_or(Path::new("."));
-            
-            if let Ok(relative_path) = cfg.image_path.strip_prefix(output_dir) {
-                let relative_str = relative_path.to_string_lossy();
-                
-                // Simple approach: since we control image generation, we can do direct replacement
-                // Look for patterns like ![alt](filename.ext) and replace with ![alt](relative_path/filename.ext)
-                let lines: Vec<&str> = markdown.lines().collect();
-                let mut result = String::new();
-                
-                for line in lines {
-                    if line.contains("![") && line.contains("](") {
-                        // Find and replace image references
-                        let mut new_line = line.to_string();
-                        
-                        // Look for image markdown pattern and extract filename
-                        if let Some(start) = line.find("](") {
-                            if let Some(end) = line[start+2..].find(")") {
-                                let path_part = &line[start+2..start+2+end];
-                                
-                                // Check if it's just a filename (no path separators)
-                                if !path_part.contains('/') && !path_part.contains('\\') && 
-                                   (path_part.ends_with(".png") || path_part.ends_with(".jpg") || 
-                                    path_part.ends_with(".jpeg") || path_part.ends_with(".gif") || 
-                                    path_part.ends_with(".webp")) {
-                                    
-                                    let new_path = format!("{}/{}", relative_str, path_part);
-                                    new_line = line.replace(&format!("]({})", path_part), &format!("]({})", new_path));
-                                }
-                            }
-                        }
-                        result.push_str(&new_line);
-                    } else {
-                        result.push_str(line);
-                    }
-                    result.push('\n');
-                }
-                
-                return Ok(result);
-            }
-        }
-    }
-    
-    // If no output path configured or empty (stdout), use absolute paths
-    let abs_path = cfg.image_path.canonicalize()
-        .unwrap_or_else(|_| cfg.image_path.clone())
-        .to_string_lossy()
-        .to_string();
-    
-    let lines: Vec<&str> = markdown.lines().collect();
-    let mut result = String::new();
-    
-    for line in lines {
-        if line.contains("![") && line.contains("](") {
-            // Find and replace image references with absolute paths
-            let mut new_line = line.to_string();
-            
-            // Look for image markdown pattern and extract filename
-            if let Some(start) = line.find("](") {
-                if let Some(end) = line[start+2..].find(")") {
-                    let path_part = &line[start+2..start+2+end];
-                    
-                    // Check if it's just a filename (no path separators)
-                    if !path_part.contains('/') && !path_part.contains('\\') && 
-                       (path_part.ends_with(".png") || path_part.ends_with(".jpg") || 
-                        path_part.ends_with(".jpeg") || path_part.ends_with(".gif") || 
-                        path_part.ends_with(".webp")) {
-                        
-                        let new_path = format!("{}/{}", abs_path, path_part);
-                        new_line = line.replace(&format!("]({})", path_part), &format!("]({})", new_path));
-                    }
-                }
-            }
-            result.push_str(&new_line);
-        } else {
-            result.push_str(line);
+    // Determine processing mode based on configuration
+    let mode = cfg.image_processing_mode();
+
+    let Some(image_data) = images.get(part_name) else {
+        if !cfg.skip_images {
+            crate::context::warn(format!("Image not found in the package: {}", part_name));
         }
-        result.push('\n');
-    }
-    
-    Ok(result)
+        return Ok(None);
+    };
+
+    // Use the image2md module to process the image with proper mode
+    let image_md = image2md::run_with_alt(image_data, mode, alt)?;
+
+    Ok(Some(image_md))
 }
 
-fn extract_text_body(reader: &mut Reader<&[u8]>) -> Result<String, String> {
-    let mut text_content = String::new();
+// Writes the paragraphs of a p:txBody into `md`; false when it held no text
+fn extract_text_body(
+    reader: &mut Reader<&[u8]>,
+    rels: &HashMap<String, Relationship>,
+    md: &mut MarkdownBuilder,
+) -> Result<bool, String> {
+    let mut has_text = false;
     let mut buf = Vec::new();
     let mut current_paragraph = String::new();
+    let mut bullet = BulletKind::Unspecified;
+    let mut current_link: Option<Hyperlink> = None;
+    let mut list_number = 0;
 
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(element)) => {
-                if element.name().as_ref() == b"a:t" {
-                    let text = extract_text_run(reader)?;
-                    current_paragraph.push_str(&text);
+                match element.name().as_ref() {
+                    b"a:t" => {
+                        let text = extract_text_run(reader)?;
+                        match &current_link {
+                            Some(link) if !text.trim().is_empty() => {
+                                current_paragraph.push_str(&link.apply(&text));
+                            }
+                            _ => current_paragraph.push_str(&text),
+                        }
+                    }
+                    b"a:hlinkClick" => current_link = resolve_hyperlink(&element, rels)?,
+                    _ => {}
+                }
+            }
+            Ok(Event::Empty(element)) => {
+                match element.name().as_ref() {
+                    b"a:hlinkClick" => current_link = resolve_hyperlink(&element, rels)?,
+                    b"a:buAutoNum" => bullet = BulletKind::Numbered,
+                    b"a:buChar" => bullet = BulletKind::Char,
+                    b"a:buNone" => bullet = BulletKind::None,
+                    _ => {}
                 }
             }
             Ok(Event::End(element)) => {
                 match element.name().as_ref() {
+                    b"a:r" => current_link = None,
                     b"a:p" => {
-                        if !current_paragraph.trim().is_empty() {
-                            if is_title_text(&current_paragraph) {
-                                text_content.push_str(&format!("### {}\n", current_paragraph.trim()));
+                        let paragraph = current_paragraph.trim();
+                        if !paragraph.is_empty() {
+                            has_text = true;
+                            if bullet == BulletKind::Numbered {
+                                list_number += 1;
+                                md.ordered_item(0, list_number, paragraph);
                             } else {
-                                text_content.push_str(&format!("- {}\n", current_paragraph.trim()));
+                                list_number = 0;
+                                if bullet == BulletKind::Char {
+                                    md.list_item(0, paragraph);
+                                } else if is_title_text(paragraph) {
+                                    crate::context::warn(format!("Title guessed from text length: {:?}", paragraph));
+                                    md.heading(3, paragraph);
+                                } else if bullet == BulletKind::None {
+                                    md.paragraph(paragraph);
+                                } else {
+                                    md.list_item(0, paragraph);
+                                }
                             }
-                            current_paragraph.clear();
                         }
+                        current_paragraph.clear();
+                        bullet = BulletKind::Unspecified;
                     }
                     b"p:txBody" => break,
                     _ => {}
@@ -290,7 +413,54 @@ fn extract_text_body(reader: &mut Reader<&[u8]>) -> Result<String, String> {
         buf.clear();
     }
 
-    Ok(text_content)
+    Ok(has_text)
+}
+
+// Resolves an a:hlinkClick: external r:id targets become URLs, slide targets
+// and `ppaction://hlinkshowjump` actions become "→ Slide N" style labels
+fn resolve_hyperlink(
+    element: &quick_xml::events::BytesStart,
+    rels: &HashMap<String, Relationship>,
+) -> Result<Option<Hyperlink>, String> {
+    let rel = opc::attribute_value(element, b"r:id")?
+        .filter(|id| !id.is_empty())
+        .and_then(|id| rels.get(&id));
+
+    if let Some(rel) = rel {
+        if rel.external {
+            return Ok(Some(Hyperlink::Url(rel.target.clone())));
+        }
+        if rel.rel_type.ends_with("/slide") {
+            return Ok(Some(Hyperlink::Slide(slide_label(&rel.target))));
+        }
+        return Ok(None);
+    }
+
+    let jump = opc::attribute_value(element, b"action")?
+        .and_then(|action| action.strip_prefix("ppaction://hlinkshowjump?jump=").map(str::to_string));
+    Ok(jump.map(|jump| {
+        let label = match jump.as_str() {
+            "nextslide" => "next slide",
+            "previousslide" => "previous slide",
+            "firstslide" => "first slide",
+            "lastslide" => "last slide",
+            "lastslideviewed" => "last viewed slide",
+            "endshow" => "end of show",
+            other => other,
+        };
+        Hyperlink::Slide(label.to_string())
+    }))
+}
+
+// `slide3.xml` -> "Slide 3"
+fn slide_label(target: &str) -> String {
+    let file_name = target.rsplit('/').next().unwrap_or(target);
+    let number: String = file_name.chars().filter(|c| c.is_ascii_digit()).collect();
+    if number.is_empty() {
+        "another slide".to_string()
+    } else {
+        format!("Slide {}", number)
+    }
 }
 
 fn extract_text_run(reader: &mut Reader<&[u8]>) -> Result<String, String> {
@@ -317,7 +487,7 @@ fn extract_text_run(reader: &mut Reader<&[u8]>) -> Result<String, String> {
     Ok(text)
 }
 
-fn extract_table(reader: &mut Reader<&[u8]>) -> Result<String, String> {
+fn extract_table(reader: &mut Reader<&[u8]>) -> Result<Vec<Vec<String>>, String> {
     let mut table = TableData { rows: vec![] };
     let mut buf = Vec::new();
     let mut current_row_index = 0;
@@ -351,7 +521,7 @@ fn extract_table(reader: &mut Reader<&[u8]>) -> Result<String, String> {
         buf.clear();
     }
 
-    Ok(format_table_as_markdown(&table))
+    Ok(table.rows)
 }
 
 fn extract_table_cell(reader: &mut Reader<&[u8]>) -> Result<String, String> {
@@ -363,11 +533,12 @@ fn extract_table_cell(reader: &mut Reader<&[u8]>) -> Result<String, String> {
             Ok(Event::Text(e)) => {
                 cell_content.push_str(&e.unescape().unwrap_or_default());
             }
-            Ok(Event::End(element)) => {
-                if element.name().as_ref() == b"a:tc" {
-                    break;
-                }
-            }
+            Ok(Event::End(element)) => match element.name().as_ref() {
+                b"a:tc" => break,
+                // Keep paragraphs apart so they don't run together
+                b"a:p" => cell_content.push('\n'),
+                _ => {}
+            },
             Ok(Event::Eof) => break,
             Err(e) => return Err(format!("Error extracting table cell: {}", e)),
             _ => {}
@@ -378,44 +549,9 @@ fn extract_table_cell(reader: &mut Reader<&[u8]>) -> Result<String, String> {
     Ok(cell_content.trim().to_string())
 }
 
-fn format_table_as_markdown(table: &TableData) -> String {
-    if table.rows.is_empty() {
-        return String::new();
-    }
-
-    let mut markdown = String::new();
-
-    // Header row
-    if !table.rows.is_empty() {
-        markdown.push('|');
-        for cell in &table.rows[0] {
-            markdown.push_str(&format!(" {} |", cell));
-        }
-        markdown.push('\n');
-
-        // Separator row
-        markdown.push('|');
-        for _ in &table.rows[0] {
-            markdown.push_str("---|");
-        }
-        markdown.push('\n');
-
-        // Data rows
-        for row in table.rows.iter().skip(1) {
-            markdown.push('|');
-            for cell in row {
-                markdown.push_str(&format!(" {} |", cell));
-            }
-            markdown.push('\n');
-        }
-    }
-
-    markdown
-}
-
 fn is_title_text(text: &str) -> bool {
     let trimmed = text.trim();
-    trimmed.len() < 100 && 
+    trimmed.chars().count() < 100 && 
     !trimmed.ends_with('.') && 
     !trimmed.ends_with('!') && 
     !trimmed.ends_with('?') &&
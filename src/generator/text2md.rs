@@ -0,0 +1,23 @@
+// Markdown / plain text input is already in its final form: pass it through
+
+pub fn run(bytes: &[u8]) -> Result<String, String> {
+    let text = String::from_utf8(bytes.to_vec())
+        .map_err(|e| format!("Invalid UTF-8 encoding: {}", e))?;
+
+    // Only undo what editors add on save; the content itself is left alone
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(&text);
+    Ok(text.replace("\r\n", "\n"))
+}
+
+/// Heuristic for extensionless input: UTF-8 without NUL bytes in the first 8 KB
+pub fn looks_like_text(bytes: &[u8]) -> bool {
+    let head = &bytes[..bytes.len().min(8192)];
+    if head.is_empty() || head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        // A multi-byte character cut off by the 8 KB window is still text
+        Err(e) => e.error_len().is_none(),
+    }
+}
@@ -0,0 +1,213 @@
+// vCard 3.0/4.0 -> one `### Name` section per contact
+
+use crate::config::SETTINGS;
+
+struct Property {
+    name: String,
+    // TYPE=... values, lower-cased (`TYPE=cell,voice` / `TYPE=cell;TYPE=voice` / bare `CELL` in 2.1)
+    types: Vec<String>,
+    value: String,
+}
+
+pub fn run(file_stream: &[u8]) -> Result<String, String> {
+    let content = String::from_utf8_lossy(file_stream);
+    let cards = parse_cards(&content);
+    if cards.is_empty() {
+        return Err("No vCard entries found".to_string());
+    }
+
+    let mut markdown = String::new();
+    for card in &cards {
+        markdown.push_str(&render_card(card));
+        markdown.push('\n');
+    }
+    Ok(markdown)
+}
+
+fn parse_cards(content: &str) -> Vec<Vec<Property>> {
+    let mut cards = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+
+    for line in unfold_lines(content) {
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut params = head.split(';');
+        let name = params.next().unwrap_or_default();
+        // Apple groups properties as `item1.EMAIL`
+        let name = name.rsplit('.').next().unwrap_or(name).to_uppercase();
+
+        match (name.as_str(), value.trim().to_uppercase().as_str()) {
+            ("BEGIN", "VCARD") => current = Some(Vec::new()),
+            ("END", "VCARD") => cards.extend(current.take()),
+            _ => {
+                if let Some(card) = current.as_mut() {
+                    let types = params
+                        .filter_map(|param| match param.split_once('=') {
+                            Some((key, value)) if key.eq_ignore_ascii_case("TYPE") => Some(value),
+                            Some(_) => None,
+                            None => Some(param),
+                        })
+                        .flat_map(|value| value.trim_matches('"').split(','))
+                        .map(|value| value.to_lowercase())
+                        .filter(|value| value != "pref" && value != "internet")
+                        .collect();
+                    card.push(Property {
+                        name,
+                        types,
+                        value: value.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    cards
+}
+
+// Lines starting with a space or tab continue the previous one (RFC 6350 §3.2)
+pub(crate) fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.lines() {
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => result.push_str(", "),
+                Some(other) => result.push(other),
+                None => {}
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// Split a structured value (ADR, N, ORG) on unescaped `;`
+fn components(value: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ';' {
+            parts.push(unescape(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    parts.push(unescape(&current));
+    parts.into_iter().map(|part| part.trim().to_string()).filter(|part| !part.is_empty()).collect()
+}
+
+fn render_card(card: &[Property]) -> String {
+    let bullet = SETTINGS.read().unwrap().bullet_char;
+    let name = card
+        .iter()
+        .find(|prop| prop.name == "FN")
+        .map(|prop| unescape(&prop.value))
+        .or_else(|| {
+            // N is family;given;additional;prefix;suffix
+            card.iter().find(|prop| prop.name == "N").map(|prop| {
+                let mut parts = components(&prop.value);
+                if parts.len() > 1 {
+                    parts.swap(0, 1);
+                }
+                parts.join(" ")
+            })
+        })
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "Unnamed contact".to_string());
+
+    let mut fields = Vec::new();
+    let mut other = Vec::new();
+    for prop in card {
+        let label = match prop.name.as_str() {
+            "TEL" => "Phone",
+            "EMAIL" => "Email",
+            "ADR" => "Address",
+            "ORG" => "Organization",
+            "TITLE" => "Title",
+            "URL" => "URL",
+            // Already used for the heading, or not meaningful as text
+            "FN" | "N" | "VERSION" | "PRODID" | "PHOTO" | "LOGO" | "SOUND" | "KEY" => continue,
+            _ => {
+                other.push(format!("{}: {}", prop.name, unescape(&prop.value)));
+                continue;
+            }
+        };
+
+        let value = match prop.name.as_str() {
+            "ADR" => components(&prop.value).join(", "),
+            "ORG" => components(&prop.value).join(" / "),
+            // vCard 4.0 writes phone numbers as `tel:` URIs
+            "TEL" => unescape(prop.value.trim_start_matches("tel:")),
+            _ => unescape(&prop.value),
+        };
+        if value.is_empty() {
+            continue;
+        }
+
+        if prop.types.is_empty() {
+            fields.push(format!("{} **{}:** {}", bullet, label, value));
+        } else {
+            fields.push(format!("{} **{} ({}):** {}", bullet, label, prop.types.join(", "), value));
+        }
+    }
+
+    let mut markdown = format!("### {}\n\n", name.trim());
+    for field in &fields {
+        markdown.push_str(field);
+        markdown.push('\n');
+    }
+    if !other.is_empty() {
+        markdown.push_str(&format!("{} **Other:**\n", bullet));
+        for item in &other {
+            markdown.push_str(&format!("  {} {}\n", bullet, item));
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vcard_contacts() {
+        let vcf = "BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Jane Doe\r\nN:Doe;Jane;;;\r\n\
+                   TEL;TYPE=CELL,PREF:+1 555 0100\r\nitem1.EMAIL;TYPE=INTERNET:jane@example.com\r\n\
+                   ADR;TYPE=WORK:;;1 Main St;Spring\r\n field;IL;62701;USA\r\nORG:Acme\\, Inc.;R&D\r\n\
+                   NOTE:Met at conf\r\nEND:VCARD\r\n\
+                   BEGIN:VCARD\r\nVERSION:4.0\r\nN:Smith;John;;;\r\nEND:VCARD\r\n";
+
+        let markdown = run(vcf.as_bytes()).unwrap();
+        assert_eq!(
+            markdown,
+            "### Jane Doe\n\n\
+             - **Phone (cell):** +1 555 0100\n\
+             - **Email:** jane@example.com\n\
+             - **Address (work):** 1 Main St, Springfield, IL, 62701, USA\n\
+             - **Organization:** Acme, Inc. / R&D\n\
+             - **Other:**\n  - NOTE: Met at conf\n\n\
+             ### John Smith\n\n\n"
+        );
+    }
+}
@@ -2,6 +2,7 @@ use hound::WavReader;
 use std::io::Cursor;
 use vosk::{Model, Recognizer};
 use crate::config::SETTINGS;
+use crate::table::render_table;
 
 // Helper function to read wave data from a byte stream
 fn retrieve_wave_samples(stream: &[u8]) -> Result<(Vec<i16>, u32), String> {
@@ -68,23 +69,100 @@ pub fn run(file_stream: &[u8]) -> Result<String, String> {
     let mut recognizer = Recognizer::new(&model, sample_rate as f32)
         .ok_or_else(|| "Recognizer initialization failed".to_string())?;
 
+    // N-best mode: Vosk returns every hypothesis (best first) instead of a single result
+    let max_alternatives = cfg.transcription_alternatives;
+    if max_alternatives > 1 {
+        recognizer.set_max_alternatives(u16::try_from(max_alternatives).unwrap_or(u16::MAX));
+    }
+    // Per-word start/end times, for captions
+    if cfg.transcription_words {
+        recognizer.set_words(true);
+    }
+
     recognizer.accept_waveform(&samples)
         .map_err(|e| format!("Failed to process audio stream: {}", e))?;
         
     let result = recognizer.final_result();
-    let text = result
-        .single()
-        .map(|alt| alt.text)
-        .unwrap_or("[No valid content recognized]");
+    let mut alternatives = String::new();
+    // (start, end, word) of the best hypothesis
+    let mut words: Vec<(f32, f32, &str)> = Vec::new();
+    let text = if max_alternatives > 1 {
+        let hypotheses = result.multiple().map(|multiple| multiple.alternatives).unwrap_or_default();
+        if hypotheses.len() > 1 {
+            alternatives.push_str("\n\n## Alternatives\n");
+            for (index, alternative) in hypotheses.iter().enumerate() {
+                alternatives.push_str(&format!(
+                    "{}. {} (confidence: {:.2})\n",
+                    index + 1,
+                    alternative.text,
+                    alternative.confidence
+                ));
+            }
+        }
+        match hypotheses.first() {
+            Some(best) => {
+                words = best.result.iter().map(|w| (w.start, w.end, w.word)).collect();
+                best.text
+            }
+            None => "[No valid content recognized]",
+        }
+    } else {
+        match result.single() {
+            Some(best) => {
+                words = best.result.iter().map(|w| (w.start, w.end, w.word)).collect();
+                best.text
+            }
+            None => "[No valid content recognized]",
+        }
+    };
+
+    let mut timestamps = String::new();
+    if cfg.transcription_words && !words.is_empty() {
+        let mut rows = vec![vec!["Start".to_string(), "End".to_string(), "Word".to_string()]];
+        rows.extend(words.iter().map(|(start, end, word)| {
+            vec![format_timestamp(*start), format_timestamp(*end), word.to_string()]
+        }));
+        timestamps.push_str("\n\n## Word Timestamps\n");
+        timestamps.push_str(&render_table(&rows, cfg.effective_table_style(), &cfg.empty_cell_placeholder));
+    }
+    let duration = samples.len() as f32 / sample_rate.max(1) as f32;
 
     Ok(format!(
         "# Audio Transcription\n\n\
         ## Basic Information\n\
         - **Sample Rate**: {} Hz\n\
+        - **Duration**: {}\n\
         - **Recognition Engine**: Vosk (Model: {})\n\n\
-        ## Transcription\n{}",
+        ## Transcription\n{}{}{}",
         sample_rate,
+        format_timestamp(duration),
         model_path, // Using model_path to indicate which model was used
-        text
+        text,
+        alternatives,
+        timestamps
     ))
 }
+
+// Seconds -> `HH:MM:SS.mmm`, the notation SRT/VTT captions use
+fn format_timestamp(seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0.0), "00:00:00.000");
+        assert_eq!(format_timestamp(1.23), "00:00:01.230");
+        assert_eq!(format_timestamp(3725.5), "01:02:05.500");
+    }
+}
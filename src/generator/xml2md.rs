@@ -0,0 +1,188 @@
+// Generic XML -> the top levels as headings, deeper elements as nested lists,
+// attributes as `@name: value` pairs
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use crate::config::SETTINGS;
+use crate::context;
+
+// Elements with children at these depths become `#`..`###` headings
+const HEADING_LEVELS: usize = 3;
+
+#[derive(Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    children: Vec<Element>,
+}
+
+pub fn run(file_stream: &[u8]) -> Result<String, String> {
+    let bytes = file_stream.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(file_stream);
+    let content = String::from_utf8_lossy(bytes);
+    let (max_depth, bullet) = {
+        let cfg = SETTINGS.read().unwrap();
+        (cfg.xml_max_depth.max(1), cfg.bullet_char)
+    };
+
+    let root = parse(&content, max_depth)?;
+    let mut markdown = String::new();
+    render_section(&root, 0, bullet, &mut markdown);
+    Ok(markdown)
+}
+
+fn parse(content: &str, max_depth: usize) -> Result<Element, String> {
+    let mut reader = Reader::from_str(content);
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+    let mut skipped = 0usize;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) if stack.len() >= max_depth => {
+                reader
+                    .read_to_end(element.name())
+                    .map_err(|e| format!("Error parsing XML: {}", e))?;
+                skipped += 1;
+            }
+            Ok(Event::Empty(_)) if stack.len() >= max_depth => skipped += 1,
+            Ok(Event::Start(element)) => stack.push(new_element(&element)?),
+            Ok(Event::Empty(element)) => {
+                let element = new_element(&element)?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            }
+            Ok(Event::End(_)) => {
+                let Some(element) = stack.pop() else {
+                    return Err("Unbalanced closing tag".to_string());
+                };
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            }
+            Ok(Event::Text(text)) => {
+                if let Some(current) = stack.last_mut() {
+                    append_text(&mut current.text, &text.unescape().unwrap_or_default());
+                }
+            }
+            Ok(Event::CData(data)) => {
+                if let Some(current) = stack.last_mut() {
+                    append_text(&mut current.text, &String::from_utf8_lossy(&data));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Error parsing XML at byte {}: {}", reader.error_position(), e)),
+            _ => {}
+        }
+    }
+
+    if skipped > 0 {
+        context::warn(format!(
+            "{} XML element(s) nested deeper than {} levels were omitted",
+            skipped, max_depth
+        ));
+    }
+    root.ok_or_else(|| "No root element found".to_string())
+}
+
+fn new_element(element: &BytesStart) -> Result<Element, String> {
+    let mut attributes = Vec::new();
+    for attr_result in element.attributes() {
+        let attr = attr_result.map_err(|e| format!("Error reading attribute: {}", e))?;
+        attributes.push((
+            String::from_utf8_lossy(attr.key.as_ref()).into_owned(),
+            attr.unescape_value().unwrap_or_default().to_string(),
+        ));
+    }
+    Ok(Element {
+        name: String::from_utf8_lossy(element.name().as_ref()).into_owned(),
+        attributes,
+        ..Default::default()
+    })
+}
+
+// Whitespace runs (indentation between tags) collapse to single spaces
+fn append_text(target: &mut String, text: &str) {
+    for word in text.split_whitespace() {
+        if !target.is_empty() {
+            target.push(' ');
+        }
+        target.push_str(word);
+    }
+}
+
+fn render_section(element: &Element, depth: usize, bullet: char, markdown: &mut String) {
+    markdown.push_str(&format!("{} {}\n\n", "#".repeat(depth + 1), element.name));
+    if !element.text.is_empty() {
+        markdown.push_str(&format!("{}\n\n", element.text));
+    }
+
+    let mut in_list = false;
+    for (key, value) in &element.attributes {
+        markdown.push_str(&format!("{} **@{}**: {}\n", bullet, key, value));
+        in_list = true;
+    }
+    for child in &element.children {
+        if !child.children.is_empty() && depth + 1 < HEADING_LEVELS {
+            if in_list {
+                markdown.push('\n');
+                in_list = false;
+            }
+            render_section(child, depth + 1, bullet, markdown);
+        } else {
+            render_item(child, 0, bullet, markdown);
+            in_list = true;
+        }
+    }
+    if in_list {
+        markdown.push('\n');
+    }
+}
+
+fn render_item(element: &Element, indent: usize, bullet: char, markdown: &mut String) {
+    let padding = "  ".repeat(indent);
+    if element.text.is_empty() {
+        markdown.push_str(&format!("{}{} **{}**\n", padding, bullet, element.name));
+    } else {
+        markdown.push_str(&format!("{}{} **{}**: {}\n", padding, bullet, element.name, element.text));
+    }
+    for (key, value) in &element.attributes {
+        markdown.push_str(&format!("{}  {} **@{}**: {}\n", padding, bullet, key, value));
+    }
+    for child in &element.children {
+        render_item(child, indent + 1, bullet, markdown);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings_lists_and_attributes() {
+        let xml = r#"<?xml version="1.0"?>
+            <catalog version="2">
+              <book id="b1">
+                <title>Rust &amp; XML</title>
+                <price currency="EUR">30</price>
+              </book>
+            </catalog>"#;
+        let mut markdown = String::new();
+        render_section(&parse(xml, 16).unwrap(), 0, '-', &mut markdown);
+        assert_eq!(
+            markdown,
+            "# catalog\n\n- **@version**: 2\n\n## book\n\n- **@id**: b1\n- **title**: Rust & XML\n\
+             - **price**: 30\n  - **@currency**: EUR\n\n"
+        );
+    }
+
+    #[test]
+    fn test_max_depth_drops_deeper_elements() {
+        let root = parse("<a><b><c><d/></c></b></a>", 2).unwrap();
+        assert_eq!(root.children[0].name, "b");
+        assert!(root.children[0].children.is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+// Plain ZIP bundle -> every supported entry converted and merged under `# <path/in/zip>`
+
+use std::io::Cursor;
+use std::path::Path;
+use zip::ZipArchive;
+use crate::context;
+use crate::formats;
+use crate::merge::{self, MergeOptions};
+use crate::opc;
+use crate::ConverterFile;
+
+pub fn run(file_stream: &[u8]) -> Result<String, String> {
+    let mut archive = ZipArchive::new(Cursor::new(file_stream))
+        .map_err(|e| format!("Failed to open ZIP archive: {}", e))?;
+
+    // Office packages are ZIPs too; one without its .docx/.pptx/.xlsx name ends up here
+    if archive.by_name("[Content_Types].xml").is_ok() {
+        return Err("Archive is an Office package; give it its .docx/.pptx/.xlsx extension".to_string());
+    }
+
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to access file in ZIP archive: {}", e))?;
+        let name = entry.name().to_string();
+        // macOS resource forks aren't part of the bundle
+        if entry.is_dir() || name.starts_with("__MACOSX/") {
+            continue;
+        }
+        if !is_convertible(&name) {
+            skipped.push(name);
+            continue;
+        }
+        files.push(ConverterFile {
+            file_path: Some(name),
+            file_stream: opc::read_entry(&mut entry)?,
+        });
+    }
+
+    if files.is_empty() && skipped.is_empty() {
+        return Err("ZIP archive is empty".to_string());
+    }
+
+    let options = MergeOptions {
+        path_headings: true,
+        skip_failures: true,
+        ..MergeOptions::default()
+    };
+    let merged = merge::merge(files, &options).map_err(|e| e.to_string())?;
+    // Always inside `convert_detailed`, so the images end up in `Conversion::images`
+    for (path, data) in &merged.images {
+        context::defer_image(path.clone(), data);
+    }
+
+    let mut markdown = merged.markdown;
+    if !skipped.is_empty() {
+        context::warn(format!("{} unsupported file(s) in the ZIP archive were skipped", skipped.len()));
+        if markdown.trim().is_empty() {
+            markdown.clear();
+        } else {
+            markdown.push_str("\n---\n\n");
+        }
+        markdown.push_str("# Skipped\n\n");
+        for name in &skipped {
+            markdown.push_str(&format!("- `{}`\n", name));
+        }
+    }
+    Ok(markdown)
+}
+
+// Known extension; nested archives are listed rather than opened
+fn is_convertible(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| extension != "zip" && formats::mime_for_extension(&extension).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_convertible() {
+        assert!(is_convertible("reports/Q1.DOCX"));
+        assert!(is_convertible("notes.md"));
+        assert!(!is_convertible("bundle/inner.zip"));
+        assert!(!is_convertible("tool.exe"));
+        assert!(!is_convertible("README"));
+    }
+
+    #[test]
+    fn test_entry_warnings_are_prefixed_once() {
+        use std::io::Write;
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in [("a.json", "{"), ("b.json", "["), ("c.txt", "fine")] {
+            zip.start_file(name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        let file = ConverterFile {
+            file_path: Some("bundle.zip".to_string()),
+            file_stream: zip.finish().unwrap().into_inner(),
+        };
+
+        let conversion = crate::convert_detailed(file).unwrap();
+        assert!(conversion.partial);
+        assert_eq!(conversion.warnings.len(), 2);
+        assert!(conversion.warnings[0].starts_with("a.json could not be converted: "));
+        assert!(conversion.warnings[1].starts_with("b.json could not be converted: "));
+        assert!(conversion.markdown.contains("# c.txt\n\nfine"));
+    }
+}
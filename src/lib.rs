@@ -1,130 +1,714 @@
 use infer;
+use std::path::{Path, PathBuf};
+use converter::xlsx_media::SheetGraphic;
+use converter::xlsx2csv::Xlsx2CsvResult;
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use serde::{Deserialize, Serialize};
 pub mod config;
 pub mod generator;
 pub mod converter;
+pub mod batch;
+pub mod error;
+pub mod opc;
+pub mod postprocess;
+pub mod codelang;
+pub mod table;
+pub mod markdown;
+pub mod formats;
+pub mod stats;
+pub mod dates;
+mod context;
+pub mod cache;
+pub mod prelude;
+pub mod merge;
+pub mod split;
+mod by_format;
+#[cfg(feature = "async")]
+pub mod async_api;
+
+pub use error::ConvertError;
+pub use formats::{supported_formats, FormatInfo};
+pub use converter::xlsx2csv::{FailedSheet, Xlsx2CsvConfig};
+pub use merge::{convert_merged, MergeOptions};
+pub use by_format::{
+    audio_to_markdown, csv_to_markdown, docx_to_markdown, eml_to_markdown, html_to_markdown,
+    ical_to_markdown, image_to_markdown, json_to_markdown, ods_to_markdown, odt_to_markdown,
+    pptx_to_markdown, text_to_markdown, vcard_to_markdown, wav_to_markdown, xlsx_to_markdown,
+    xml_to_markdown, zip_to_markdown,
+};
+#[cfg(feature = "async")]
+pub use async_api::convert_async;
+use formats::FormatKind;
+
+/// Appended when output hits `Settings.max_output_bytes`
+pub(crate) const TRUNCATION_NOTICE: &str = "\n\n> [output truncated]\n";
+
+/// Ends a `convert_preview` that was cut short
+pub const PREVIEW_TRUNCATION_MARKER: &str = "\n\n…(truncated)\n";
+
+/// Markdown plus metadata about the converted document
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Conversion {
+    pub markdown: String,
+    pub word_count: usize,
+    /// Estimated at `stats::WORDS_PER_MINUTE`
+    pub reading_minutes: f32,
+    /// Document properties (title, author, dates...) where the format records them (DOCX/PPTX)
+    pub metadata: Option<opc::CoreProperties>,
+    /// Images the Markdown links to in SaveToFile mode, as (path relative to
+    /// `Settings.image_path`, bytes). Nothing is written to disk; see `write_images`.
+    pub images: Vec<(PathBuf, Vec<u8>)>,
+    /// Lossy or heuristic decisions taken during the conversion (guessed headings,
+    /// dropped images...) and recovered failures, e.g. PPTX slides that could not be parsed
+    pub warnings: Vec<String>,
+    /// Some part of the document (an XLSX sheet, a PPTX slide, a picture...) failed and
+    /// was left out; the rest was converted. The failures are among `warnings`.
+    pub partial: bool,
+}
 
 pub struct ConverterFile {
     pub file_path: Option<String>,
     pub file_stream: Vec<u8>,
 }
 
+/// Explicit settings for `convert_bytes_as`, in place of the process-wide `SETTINGS`
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    pub settings: config::Settings,
+    /// Where the bytes came from, for `{source_stem}` in image names; never read
+    pub file_path: Option<String>,
+}
+
+impl ConvertOptions {
+    pub fn new(settings: config::Settings) -> Self {
+        Self { settings, file_path: None }
+    }
+}
+
+/// The built-in defaults: no Config.toml, environment or `init_settings` involved
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self::new(config::Settings::builtin())
+    }
+}
+
 // Helper function to determine file type from extension
 fn get_file_type_from_extension(file_path: &Option<String>) -> Option<&'static str> {
     let path = file_path.as_ref()?;
     let extension = std::path::Path::new(path)
         .extension()?
-        .to_str()?
-        .to_lowercase();
-
-    match extension.as_str() {
-        "docx" => Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
-        "xlsx" => Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
-        "pptx" => Some("application/vnd.openxmlformats-officedocument.presentationml.presentation"),
-        "csv" => Some("text/csv"),
-        "wav" => Some("audio/wav"),
-        "jpg" | "jpeg" => Some("image/jpeg"),
-        "png" => Some("image/png"),
-        "gif" => Some("image/gif"),
-        "html" | "htm" => Some("text/html"),
-        _ => None,
+        .to_str()?;
+
+    formats::mime_for_extension(extension)
+}
+
+// `sniffed` is what `infer::get` made of the bytes (None: no known signature); the
+// checks below refine it with the extension and the package contents
+fn detect_mime_type(file: &ConverterFile, sniffed: Option<infer::Type>) -> Result<&'static str, ConvertError> {
+    // `infer` only knows binary signatures, so text formats (CSV, HTML, SVG...)
+    // usually come back as None; try the extension before giving up.
+    let mut mime_type = match sniffed {
+        Some(kind) => kind.mime_type(),
+        None => match get_file_type_from_extension(&file.file_path) {
+            Some(mime) => mime,
+            // SVG is XML text: extensionless (stdin), only its root element tells
+            None if generator::image2md::is_svg_document(&file.file_stream) => "image/svg+xml",
+            // Unrecognised but readable text is passed through rather than rejected
+            None if generator::text2md::looks_like_text(&file.file_stream)
+                && !config::SETTINGS.read().unwrap().strict => "text/plain",
+            None => return Err(ConvertError::UnknownType),
+        },
+    };
+
+    // `<?xml` sniffs as text/xml and `.xml` maps to generic XML, but it may still be an SVG
+    if matches!(mime_type, "text/xml" | "application/xml")
+        && generator::image2md::is_svg_document(&file.file_stream)
+    {
+        mime_type = "image/svg+xml";
     }
+
+    // iWork packages are ZIPs too; say what they are instead of a generic "application/zip"
+    if mime_type == "application/zip" && converter::iwork::is_iwork(&file.file_stream) {
+        let extension = file
+            .file_path
+            .as_ref()
+            .and_then(|path| Path::new(path).extension())
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        return Err(ConvertError::UnsupportedType(
+            converter::iwork::describe(&extension).to_string(),
+        ));
+    }
+
+    // Fallback to extension-based detection for ZIP files (Office documents) and text files
+    if (mime_type == "application/zip" || mime_type == "text/plain")
+        && let Some(extension_mime) = get_file_type_from_extension(&file.file_path)
+    {
+        mime_type = extension_mime;
+    }
+
+    // Still a plain ZIP (no extension, or `.zip`/`.bin` on an Office file): look for the main part
+    if mime_type == "application/zip"
+        && let Some(package_mime) = opc::package_mime_type(&file.file_stream)
+    {
+        mime_type = package_mime;
+    }
+
+    Ok(mime_type)
 }
 
 // byte_stream -> String
-pub fn convert(file: ConverterFile) -> Result<String, String> {
-    let kind = infer::get(&file.file_stream)
-        .ok_or_else(|| "Could not determine file type".to_string())?;
+//
+// `Settings.force_mime` (APP__FORCE_MIME) bypasses every detection step below,
+// including the legacy-format check; the input is converted as that type.
+pub fn convert(file: ConverterFile) -> Result<String, ConvertError> {
+    match infer::get(&file.file_stream) {
+        Some(kind) => convert_with_detected(file, kind),
+        None => save_images(convert_sniffed(file, None)?),
+    }
+}
+
+/// `convert` for bytes the caller has already run through `infer::get` (e.g. an
+/// upload handler's content check): `kind` is used instead of sniffing again.
+/// The extension and package checks that refine a generic ZIP/text type still apply.
+pub fn convert_with_detected(file: ConverterFile, kind: infer::Type) -> Result<String, ConvertError> {
+    save_images(convert_sniffed(file, Some(kind))?)
+}
 
-    let mut mime_type = kind.mime_type();
+/// Like `convert`, but also returns document statistics and leaves the
+/// SaveToFile images in `Conversion::images` instead of writing them
+pub fn convert_detailed(file: ConverterFile) -> Result<Conversion, ConvertError> {
+    let sniffed = infer::get(&file.file_stream);
+    convert_sniffed(file, sniffed)
+}
 
-    // Fallback to extension-based detection for ZIP files (Office documents) and text files
-    if mime_type == "application/zip" || mime_type == "text/plain" {
-        if let Some(extension_mime) = get_file_type_from_extension(&file.file_path) {
-            mime_type = extension_mime;
+fn save_images(conversion: Conversion) -> Result<String, ConvertError> {
+    let image_path = config::SETTINGS.read().unwrap().image_path.clone();
+    write_images(&image_path, &conversion.images)?;
+    Ok(conversion.markdown)
+}
+
+fn convert_sniffed(file: ConverterFile, sniffed: Option<infer::Type>) -> Result<Conversion, ConvertError> {
+    check_input_size(file.file_stream.len())?;
+    let _scope = context::Scope::begin();
+    context::set_source_stem(file.file_path.as_deref());
+
+    let forced_mime = config::SETTINGS
+        .read()
+        .unwrap()
+        .force_mime
+        .clone()
+        .filter(|mime| !mime.is_empty());
+
+    let mime_type: &str = match &forced_mime {
+        Some(mime) => mime,
+        None => {
+            // Pre-2007 Office files are OLE2 containers, not ZIPs; catch them before
+            // they reach the OOXML readers and fail with a misleading archive error
+            if converter::ole2ooxml::is_ole2(&file.file_stream) {
+                return convert_legacy(file);
+            }
+            detect_mime_type(&file, sniffed)?
         }
-    }
+    };
 
-    if cfg!(debug_assertions) {
-        dbg!(mime_type);
+    convert_as(&file.file_stream, mime_type)
+}
+
+/// Convert `bytes` as `mime` (e.g. an HTTP `Content-Type`; parameters such as
+/// `; charset=` are ignored) with `opts.settings` instead of the process-wide `SETTINGS`.
+/// Nothing is detected and no global state is read or changed, so the result depends on
+/// the arguments alone. `convert` runs the same conversion once it has detected the type.
+/// `opts.settings` apply to the calling thread: a generator that fans work out to other
+/// threads passes its settings along explicitly (see `converter::xlsx2csv`).
+pub fn convert_bytes_as(bytes: &[u8], mime: &str, opts: &ConvertOptions) -> Result<Conversion, ConvertError> {
+    let mime = mime.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    config::with_settings(opts.settings.clone(), || {
+        check_input_size(bytes.len())?;
+        let _scope = context::Scope::begin();
+        context::set_source_stem(opts.file_path.as_deref());
+        convert_as(bytes, &mime)
+    })
+}
+
+// Everything after type detection
+fn convert_as(file_stream: &[u8], mime_type: &str) -> Result<Conversion, ConvertError> {
+    // Shares the caller's scope when there is one
+    let _scope = context::Scope::begin();
+    log::debug!("detected MIME type: {}", mime_type);
+
+    let kind = formats::format_for_mime(mime_type)
+        .map(|format| format.kind)
+        .ok_or_else(|| ConvertError::UnsupportedType(mime_type.to_string()))?;
+    convert_kind(file_stream, kind)
+}
+
+// Everything after the format is known; also the per-format entry points (`by_format`)
+fn convert_kind(file_stream: &[u8], kind: FormatKind) -> Result<Conversion, ConvertError> {
+    let _scope = context::Scope::begin();
+
+    // Zip bombs are refused before any converter inflates a part
+    if matches!(
+        kind,
+        FormatKind::Docx
+            | FormatKind::Pptx
+            | FormatKind::Xlsx
+            | FormatKind::Odt
+            | FormatKind::Ods
+            | FormatKind::Zip
+    ) {
+        opc::check_archive_limits(file_stream)?;
     }
 
-    match mime_type {
-        "audio/x-wav" | "audio/wav" | "audio/wave" => {
-            generator::wav2md::run(&file.file_stream)
+    let markdown = match kind {
+        FormatKind::Wav => {
+            generator::wav2md::run(file_stream)
                 .map_err(|e| format!("Failed to convert WAV: {}", e))
         }
-        "audio/mpeg" | "audio/mp3" | "audio/flac" | "audio/ogg" | "audio/aac" | "audio/x-m4a" => {
+        FormatKind::Audio => {
             // Convert other audio formats to WAV first
-            let wav_data = converter::audio2wav::audio_to_wav(&file.file_stream)
+            let wav_data = converter::audio2wav::audio_to_wav(file_stream)
                 .map_err(|e| format!("Failed to convert audio to WAV: {:?}", e))?;
 
-            // printf information when debug
-            if cfg!(debug_assertions) {
-                dbg!(wav_data.len());
-            }
+            log::debug!("decoded WAV: {} bytes", wav_data.len());
             
             generator::wav2md::run(&wav_data)
                 .map_err(|e| format!("Failed to convert WAV: {}", e))
         }
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
-            generator::docx2md::run(&file.file_stream)
+        FormatKind::Docx => {
+            generator::docx2md::run(file_stream)
                 .map_err(|e| format!("Failed to convert DOCX: {}", e))
         }
-        "image/jpeg" | "image/png" | "image/gif" => {
-            generator::image2md::run(&file.file_stream)
+        FormatKind::Image => {
+            generator::image2md::run(file_stream)
                 .map_err(|e| format!("Failed to convert image: {}", e))
         }
-        "application/vnd.openxmlformats-officedocument.presentationml.presentation" => {
-            generator::pptx2md::run(&file.file_stream)
+        FormatKind::Pptx => {
+            generator::pptx2md::run(file_stream)
                 .map_err(|e| format!("Failed to convert PPTX: {}", e))
         }
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => {
-            let csvs = converter::xlsx2csv::xlsx_to_csv(&file.file_stream, None)
-                .map_err(|e| format!("Failed to convert XLSX: {}", e))?;
-            
-            let mut combined_md = String::new();
-            
-            for (name, csv) in csvs.sheet_names.iter().zip(csvs.csv_data.iter()) {
-                if cfg!(debug_assertions) {
-                    dbg!(name);
-                }
-                let md = generator::csv2md::run(csv.as_bytes())
-                    .map_err(|e| format!("Failed to convert CSV for sheet '{}': {}", name, e))?;
-                
-                // Add sheet name as header and the markdown content
-                if !combined_md.is_empty() {
-                    combined_md.push_str("\n\n---\n\n");
-                }
-                combined_md.push_str(&format!("## Sheet: {}\n\n", name));
-                combined_md.push_str(&md);
-            }
-            
-            if combined_md.is_empty() {
-                Err("No sheets found in XLSX file".to_string())
-            } else {
-                Ok(combined_md)
-            }
+        FormatKind::Xlsx => {
+            convert_xlsx(file_stream)
         }
-        "text/csv" | "application/csv" => {
-            generator::csv2md::run(&file.file_stream)
+        FormatKind::Csv => {
+            generator::csv2md::run(file_stream)
                 .map_err(|e| format!("Failed to convert CSV: {}", e))
         }
-        "text/html" => {
-            generator::html2md::run(&file.file_stream)
+        FormatKind::Json => {
+            generator::json2md::run(file_stream)
+                .map_err(|e| format!("Failed to convert JSON: {}", e))
+        }
+        FormatKind::Xml => {
+            generator::xml2md::run(file_stream)
+                .map_err(|e| format!("Failed to convert XML: {}", e))
+        }
+        FormatKind::Html => {
+            generator::html2md::run(file_stream)
                 .map_err(|e| format!("Failed to convert HTML: {}", e))
         }
-        _ => Err(format!("Unsupported file type: {}", mime_type)),
+        FormatKind::Ods => {
+            let config = sheet_csv_config();
+            let delimiter = config.delimiter;
+            converter::ods2csv::ods_to_csv(file_stream, Some(config))
+                .map_err(|e| format!("Failed to convert ODS: {}", e))
+                .and_then(|csvs| combine_sheets(csvs, HashMap::new(), delimiter))
+        }
+        FormatKind::Vcard => {
+            generator::vcard2md::run(file_stream)
+                .map_err(|e| format!("Failed to convert vCard: {}", e))
+        }
+        FormatKind::Ical => {
+            generator::ical2md::run(file_stream)
+                .map_err(|e| format!("Failed to convert iCalendar: {}", e))
+        }
+        FormatKind::Eml => {
+            generator::eml2md::run(file_stream)
+                .map_err(|e| format!("Failed to convert email: {}", e))
+        }
+        FormatKind::Zip => {
+            generator::zip2md::run(file_stream)
+                .map_err(|e| format!("Failed to convert ZIP archive: {}", e))
+        }
+        FormatKind::Text => {
+            generator::text2md::run(file_stream)
+                .map_err(|e| format!("Failed to read text: {}", e))
+        }
+        FormatKind::Odt => {
+            generator::odt2md::run(file_stream)
+                .map_err(|e| format!("Failed to convert ODT: {}", e))
+        }
+    };
+
+    let mut markdown = markdown.map_err(ConvertError::Conversion)?;
+
+    let image_definitions = context::take_image_definitions();
+    if !image_definitions.is_empty() {
+        markdown = format!("{}\n\n{}", markdown.trim_end(), image_definitions);
+    }
+    if config::SETTINGS.read().unwrap().normalize_typography {
+        markdown = postprocess::normalize_typography(&markdown);
+    }
+    if config::SETTINGS.read().unwrap().tidy_blank_lines {
+        markdown = postprocess::tidy_blank_lines(&markdown);
+    }
+    if config::SETTINGS.read().unwrap().lint_output {
+        markdown = postprocess::lint_markdown(&markdown);
     }
+    if let Some(limit) = output_limit() {
+        truncate_output(&mut markdown, limit);
+    }
+
+    // Word's own count is authoritative for DOCX; everything else is counted from the output
+    let word_count = match kind {
+        FormatKind::Docx => generator::docx2md::app_word_count(file_stream),
+        _ => None,
+    }
+    .unwrap_or_else(|| stats::word_count(&markdown));
+    let metadata = match kind {
+        FormatKind::Docx | FormatKind::Pptx => opc::core_properties(file_stream),
+        _ => None,
+    };
+
+    Ok(Conversion {
+        markdown,
+        word_count,
+        reading_minutes: stats::reading_minutes(word_count),
+        metadata,
+        images: context::take_images(),
+        warnings: context::take_warnings(),
+        partial: context::take_partial(),
+    })
 }
 
-pub fn convert_from_path(file_path: &str) -> Result<String, String> {
-    let file_stream = std::fs::read(file_path)
-        .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
+/// Write `Conversion::images` under `dir`, creating directories as needed
+pub fn write_images(dir: &Path, images: &[(PathBuf, Vec<u8>)]) -> Result<(), ConvertError> {
+    for (relative_path, data) in images {
+        let file_path = dir.join(relative_path);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                ConvertError::Io(format!("Failed to create image directory: {}", e))
+            })?;
+        }
+        std::fs::write(&file_path, data).map_err(|e| {
+            ConvertError::Io(format!("Failed to save image file {}: {}", file_path.display(), e))
+        })?;
+    }
+    Ok(())
+}
 
-    let file = ConverterFile {
-        file_path: Some(file_path.to_string()),
+// `Settings.max_input_bytes` guard, applied before any processing
+fn check_input_size(size: usize) -> Result<(), ConvertError> {
+    match config::SETTINGS.read().unwrap().max_input_bytes {
+        Some(limit) if size > limit => Err(ConvertError::TooLarge { size, limit }),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn output_limit() -> Option<usize> {
+    let configured = config::SETTINGS.read().unwrap().max_output_bytes;
+    match (configured, context::output_limit()) {
+        (Some(configured), Some(preview)) => Some(configured.min(preview)),
+        (configured, preview) => configured.or(preview),
+    }
+}
+
+/// Cut `markdown` to `limit` bytes and append the truncation notice.
+/// Idempotent, so generators and `convert` can both apply it.
+/// Returns true when the text was truncated.
+pub(crate) fn truncate_output(markdown: &mut String, limit: usize) -> bool {
+    if markdown.len() <= limit {
+        return false;
+    }
+    let mut cut = limit;
+    while !markdown.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    markdown.truncate(cut);
+    markdown.push_str(TRUNCATION_NOTICE);
+    true
+}
+
+// `Settings.csv_delimiter`/`csv_use_header` as the per-sheet CSV config
+fn sheet_csv_config() -> Xlsx2CsvConfig {
+    let cfg = config::SETTINGS.read().unwrap();
+    let mut sheet_config = Xlsx2CsvConfig::builder().use_header(cfg.csv_use_header);
+    if let Some(delimiter) = cfg.csv_delimiter {
+        sheet_config = sheet_config.delimiter(delimiter);
+    }
+    sheet_config
+}
+
+fn convert_xlsx(file_stream: &[u8]) -> Result<String, String> {
+    let config = sheet_csv_config();
+    let delimiter = config.delimiter;
+    let csvs = converter::xlsx2csv::xlsx_to_csv(file_stream, Some(config))
+        .map_err(|e| format!("Failed to convert XLSX: {}", e))?;
+
+    // Pictures and charts are best-effort: a broken drawing shouldn't lose the cell data
+    let graphics = converter::xlsx_media::sheet_graphics(file_stream).unwrap_or_else(|e| {
+        context::part_failed(format!("Pictures and charts could not be read: {}", e));
+        HashMap::new()
+    });
+
+    let mut markdown = combine_sheets(csvs, graphics, delimiter)?;
+    generator::append_attachments(&mut markdown, file_stream, "xl/embeddings/");
+    Ok(markdown)
+}
+
+// One `## Sheet:` section per sheet, followed by that sheet's graphics
+fn combine_sheets(
+    csvs: Xlsx2CsvResult,
+    mut graphics: HashMap<String, Vec<SheetGraphic>>,
+    delimiter: u8,
+) -> Result<String, String> {
+    let mut combined_md = String::new();
+    let limit = output_limit();
+
+    // Converted sheets, with the failed ones put back at their workbook position
+    let mut sheets: Vec<(&String, Result<&String, &String>)> =
+        csvs.sheet_names.iter().zip(csvs.csv_data.iter().map(Ok)).collect();
+    for failed in &csvs.failed_sheets {
+        let index = failed.index.min(sheets.len());
+        sheets.insert(index, (&failed.name, Err(&failed.error)));
+    }
+
+    for (name, csv) in sheets {
+        log::debug!("converting sheet: {}", name);
+        // Add sheet name as header and the markdown content
+        if !combined_md.is_empty() {
+            combined_md.push_str("\n\n---\n\n");
+        }
+        combined_md.push_str(&format!("## Sheet: {}\n\n", name));
+
+        let md = match csv {
+            Ok(csv) => generator::csv2md::run_with_delimiter(csv.as_bytes(), delimiter),
+            Err(error) => Err(error.clone()),
+        };
+        match md {
+            Ok(md) => combined_md.push_str(&md),
+            // One bad sheet shouldn't cost the rest of the workbook
+            Err(e) => {
+                let message = format!("Sheet '{}' could not be converted: {}", name, e);
+                combined_md.push_str(&format!("> [{}]\n", message));
+                context::part_failed(message);
+            }
+        }
+
+        for graphic in graphics.remove(name).unwrap_or_default() {
+            let graphic_md = match graphic {
+                SheetGraphic::Image { data, .. } => match generator::image2md::run(&data) {
+                    Ok(image_md) => image_md,
+                    Err(e) => {
+                        let message = format!("An image in sheet '{}' could not be converted: {}", name, e);
+                        context::part_failed(message);
+                        continue;
+                    }
+                },
+                SheetGraphic::Chart { name, title } => {
+                    format!("![chart: {}]({})", title.as_deref().unwrap_or("untitled"), name)
+                }
+            };
+            if graphic_md.is_empty() {
+                continue;
+            }
+            combined_md.push('\n');
+            combined_md.push_str(&graphic_md);
+            combined_md.push('\n');
+        }
+
+        if let Some(limit) = limit
+            && truncate_output(&mut combined_md, limit)
+        {
+            break;
+        }
+    }
+
+    if combined_md.is_empty() {
+        Err("No sheets found in spreadsheet".to_string())
+    } else {
+        Ok(combined_md)
+    }
+}
+
+// .doc/.xls/.ppt: convert through LibreOffice when installed, otherwise explain why we stopped
+fn convert_legacy(file: ConverterFile) -> Result<Conversion, ConvertError> {
+    let extension = file
+        .file_path
+        .as_ref()
+        .and_then(|path| Path::new(path).extension())
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    // Outlook's .msg is OLE2 as well, but not an Office document LibreOffice can open
+    if extension == "msg" {
+        return Err(ConvertError::UnsupportedType(
+            "Outlook message (.msg); save it as .eml (File > Save As) to convert it".to_string(),
+        ));
+    }
+
+    let kind = match extension.as_str() {
+        "doc" => "Word 97-2003 document (.doc)",
+        "xls" => "Excel 97-2003 workbook (.xls)",
+        "ppt" => "PowerPoint 97-2003 presentation (.ppt)",
+        _ => "OLE2 compound document",
+    };
+
+    let modern_extension = match converter::ole2ooxml::modern_extension(&extension) {
+        Some(ext) if converter::ole2ooxml::is_libreoffice_available() => ext,
+        _ => return Err(ConvertError::LegacyBinaryFormat(kind.to_string())),
+    };
+
+    let file_stream = converter::ole2ooxml::convert_with_libreoffice(&file.file_stream, &extension)
+        .map_err(|e| ConvertError::Conversion(format!("Failed to convert {}: {}", kind, e)))?;
+
+    convert_detailed(ConverterFile {
+        file_path: file.file_path.map(|path| {
+            Path::new(&path)
+                .with_extension(modern_extension)
+                .to_string_lossy()
+                .into_owned()
+        }),
         file_stream,
+    })
+}
+
+/// A quick first look at `file`: the Markdown cut to `max_chars` characters and
+/// ending in `PREVIEW_TRUNCATION_MARKER` when cut. Generators stop once the budget
+/// is spent, so on large inputs this returns long before `convert` would; images
+/// are not written. Run the full conversion afterwards to replace it.
+pub fn convert_preview(file: ConverterFile, max_chars: usize) -> Result<String, ConvertError> {
+    let _scope = context::Scope::begin();
+    // A character is at most 4 bytes in UTF-8
+    context::set_output_limit(Some(max_chars.saturating_mul(4)));
+
+    let mut markdown = convert_detailed(file)?.markdown;
+    if let Some((cut, _)) = markdown.char_indices().nth(max_chars) {
+        markdown.truncate(cut);
+        markdown.push_str(PREVIEW_TRUNCATION_MARKER);
+    }
+    Ok(markdown)
+}
+
+/// `convert` with a cache in front: identical input bytes converted with the
+/// same options return the stored Markdown instead of converting again. The
+/// conversion's images are cached with it and written again on a hit.
+pub fn convert_cached(file: ConverterFile) -> Result<String, ConvertError> {
+    save_images(convert_cached_detailed(file)?)
+}
+
+/// `convert_detailed` behind the cache of `convert_cached`; a hit returns the
+/// stored conversion, images and warnings included
+pub fn convert_cached_detailed(file: ConverterFile) -> Result<Conversion, ConvertError> {
+    let key = cache::cache_key(&file.file_stream, file.file_path.as_deref());
+    if let Some(conversion) = cache::get(&key) {
+        return Ok(conversion);
+    }
+
+    let conversion = convert_detailed(file)?;
+    cache::put(&key, &conversion);
+    Ok(conversion)
+}
+
+/// Forget everything `convert_cached` has stored
+pub fn clear_cache() {
+    cache::clear();
+}
+
+/// Run the normal conversion, then render the Markdown to HTML
+pub fn convert_to_html(file: ConverterFile) -> Result<String, ConvertError> {
+    convert(file).map(|markdown| markdown_to_html(&markdown))
+}
+
+/// Render Markdown (CommonMark plus tables, footnotes, strikethrough and task lists) to HTML
+pub fn markdown_to_html(markdown: &str) -> String {
+    use pulldown_cmark::{html, Options, Parser};
+
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS;
+    let mut output = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut output, Parser::new_ext(markdown, options));
+    output
+}
+
+/// Pull every embedded media file out of a DOCX/PPTX/XLSX package without converting
+/// the text. Files are named like converted images (AI description or `pic-{timestamp}`,
+/// de-duplicated with a `-N` suffix) and written to `Settings.image_path` when it is set.
+/// Returns the file names with their bytes.
+pub fn extract_images(file: ConverterFile) -> Result<Vec<(String, Vec<u8>)>, ConvertError> {
+    check_input_size(file.file_stream.len())?;
+    let mime_type = detect_mime_type(&file, infer::get(&file.file_stream))?;
+    let media_dir = match formats::format_for_mime(mime_type).map(|format| format.kind) {
+        Some(FormatKind::Docx) => "word/media/",
+        Some(FormatKind::Pptx) => "ppt/media/",
+        Some(FormatKind::Xlsx) => "xl/media/",
+        _ => return Err(ConvertError::UnsupportedType(mime_type.to_string())),
     };
+    opc::check_archive_limits(&file.file_stream)?;
+    // Image name templates number and name images per input
+    let _scope = context::Scope::begin();
+    context::set_source_stem(file.file_path.as_deref());
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(&file.file_stream))
+        .map_err(|e| ConvertError::Conversion(format!("Failed to open archive: {}", e)))?;
+    let media = opc::read_parts_under(&mut archive, media_dir).map_err(ConvertError::Conversion)?;
+
+    let image_path = config::SETTINGS.read().unwrap().image_path.clone();
+    if !image_path.as_os_str().is_empty() {
+        std::fs::create_dir_all(&image_path).map_err(|e| {
+            ConvertError::Io(format!("Failed to create image directory: {}", e))
+        })?;
+    }
+
+    let mut used_names = HashSet::new();
+    let mut extracted = Vec::new();
+    for (part_name, data) in media {
+        // Keep the package's extension: media may also be EMF/WMF or video
+        let extension = Path::new(&part_name)
+            .extension()
+            .map(|ext| generator::image2md::sanitize_file_name(&ext.to_string_lossy().to_lowercase()))
+            .unwrap_or_else(|| "bin".to_string());
+        let base_name = generator::image2md::generate_image_name(&data);
+
+        let mut file_name = format!("{}.{}", base_name, extension);
+        let mut suffix = 1;
+        while !used_names.insert(file_name.clone()) {
+            suffix += 1;
+            file_name = format!("{}-{}.{}", base_name, suffix, extension);
+        }
+
+        if !image_path.as_os_str().is_empty() {
+            std::fs::write(image_path.join(&file_name), &data).map_err(|e| {
+                ConvertError::Io(format!("Failed to save image file {}: {}", file_name, e))
+            })?;
+        }
+        extracted.push((file_name, data));
+    }
+
+    Ok(extracted)
+}
+
+pub fn convert_from_path(file_path: &str) -> Result<String, ConvertError> {
+    let conversion = convert_from_path_detailed(file_path)?;
+    let image_path = config::SETTINGS.read().unwrap().image_path.clone();
+    write_images(&image_path, &conversion.images)?;
+    Ok(conversion.markdown)
+}
 
-    convert(file)
+/// `convert_detailed` for a file on disk
+pub fn convert_from_path_detailed(file_path: &str) -> Result<Conversion, ConvertError> {
+    // Check the size on disk so an oversized file is never read into memory
+    let metadata = std::fs::metadata(file_path)
+        .map_err(|e| ConvertError::Io(format!("Failed to read file {}: {}", file_path, e)))?;
+    check_input_size(usize::try_from(metadata.len()).unwrap_or(usize::MAX))?;
+
+    let file_stream = std::fs::read(file_path)
+        .map_err(|e| ConvertError::Io(format!("Failed to read file {}: {}", file_path, e)))?;
+
+    convert_detailed(ConverterFile {
+        file_path: Some(file_path.to_string()),
+        file_stream,
+    })
 }
\ No newline at end of file
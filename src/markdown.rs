@@ -0,0 +1,182 @@
+//! `MarkdownBuilder`: block-level Markdown with the spacing and escaping rules in
+//! one place, so generators don't each hand-roll `push_str("\n\n")`.
+//!
+//! Blocks are separated by one blank line; consecutive list items stay together.
+//! Text passed to `paragraph`/`list_item` is inline Markdown and written as-is.
+
+use crate::config::SETTINGS;
+use crate::table::{render_table, TableStyle};
+use serde::{Deserialize, Serialize};
+
+/// Which Markdown dialect the output targets (`Settings.flavor`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkdownFlavor {
+    /// Plain CommonMark: tables as HTML, `<del>` for struck text, checkbox glyphs for tasks
+    CommonMark,
+    /// GitHub Flavored Markdown: pipe tables, `~~strike~~`, `- [x]` task lists
+    Gfm,
+}
+
+impl MarkdownFlavor {
+    /// Markup around deleted/struck-through text
+    pub fn strikethrough(self) -> (&'static str, &'static str) {
+        match self {
+            MarkdownFlavor::Gfm => ("~~", "~~"),
+            MarkdownFlavor::CommonMark => ("<del>", "</del>"),
+        }
+    }
+
+    /// What follows the bullet of a checklist item
+    pub fn task_marker(self, checked: bool) -> &'static str {
+        match (self, checked) {
+            (MarkdownFlavor::Gfm, true) => "[x]",
+            (MarkdownFlavor::Gfm, false) => "[ ]",
+            (MarkdownFlavor::CommonMark, true) => "\u{2612}",
+            (MarkdownFlavor::CommonMark, false) => "\u{2610}",
+        }
+    }
+}
+
+pub struct MarkdownBuilder {
+    out: String,
+    // the last block was a list item, so the next one continues the list
+    in_list: bool,
+    bullet: char,
+    table_style: TableStyle,
+    empty_cell: String,
+}
+
+impl Default for MarkdownBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownBuilder {
+    /// Empty document using `Settings.bullet_char`, `table_style` (as the flavor allows)
+    /// and `empty_cell_placeholder`
+    pub fn new() -> Self {
+        let cfg = SETTINGS.read().unwrap();
+        Self {
+            out: String::new(),
+            in_list: false,
+            bullet: cfg.bullet_char,
+            table_style: cfg.effective_table_style(),
+            empty_cell: cfg.empty_cell_placeholder.clone(),
+        }
+    }
+
+    /// `#`..`######` heading; `level` is clamped to 1-6 and the text kept on one line
+    pub fn heading(&mut self, level: usize, text: &str) -> &mut Self {
+        let text = single_line(text);
+        if !text.is_empty() {
+            self.push_block(&format!("{} {}", "#".repeat(level.clamp(1, 6)), text), false);
+        }
+        self
+    }
+
+    pub fn paragraph(&mut self, text: &str) -> &mut Self {
+        self.push_block(text.trim(), false);
+        self
+    }
+
+    /// Unordered item; `level` 0 is the top level, each level below indents two spaces
+    pub fn list_item(&mut self, level: usize, text: &str) -> &mut Self {
+        let text = text.trim();
+        if !text.is_empty() {
+            self.push_block(&format!("{}{} {}", "  ".repeat(level), self.bullet, text), true);
+        }
+        self
+    }
+
+    /// `1.`-style item; nested levels indent three spaces to line up with the text
+    pub fn ordered_item(&mut self, level: usize, number: usize, text: &str) -> &mut Self {
+        let text = text.trim();
+        if !text.is_empty() {
+            self.push_block(&format!("{}{}. {}", "   ".repeat(level), number, text), true);
+        }
+        self
+    }
+
+    /// Table with `headers` as the first row, rendered per `Settings.table_style`
+    pub fn table(&mut self, headers: &[String], rows: &[Vec<String>]) -> &mut Self {
+        let all_rows: Vec<Vec<String>> = std::iter::once(headers.to_vec()).chain(rows.iter().cloned()).collect();
+        let table = render_table(&all_rows, self.table_style, &self.empty_cell);
+        self.push_block(&table, false);
+        self
+    }
+
+    /// `![alt](src)` on its own line; brackets in `alt` are escaped and a `src`
+    /// with spaces or parentheses is wrapped in `<>`
+    pub fn image(&mut self, alt: &str, src: &str) -> &mut Self {
+        let alt = single_line(alt).replace('[', "\\[").replace(']', "\\]");
+        let src = if src.contains([' ', '(', ')']) { format!("<{}>", src) } else { src.to_string() };
+        self.push_block(&format!("![{}]({})", alt, src), false);
+        self
+    }
+
+    /// Thematic break (`---`)
+    pub fn rule(&mut self) -> &mut Self {
+        self.push_block("---", false);
+        self
+    }
+
+    /// Already-rendered Markdown (e.g. from another generator) as one block
+    pub fn raw(&mut self, markdown: &str) -> &mut Self {
+        self.push_block(markdown.trim_matches('\n'), false);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.out.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.out.is_empty()
+    }
+
+    /// The document, ending in a single newline (empty when nothing was added)
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    fn push_block(&mut self, block: &str, list_item: bool) {
+        let block = block.trim_end_matches('\n');
+        if block.trim().is_empty() {
+            return;
+        }
+        // `out` always ends in one newline; a second one makes the blank line
+        if !self.out.is_empty() && (!list_item || !self.in_list) {
+            self.out.push('\n');
+        }
+        self.out.push_str(block);
+        self.out.push('\n');
+        self.in_list = list_item;
+    }
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_spacing() {
+        let mut md = MarkdownBuilder::new();
+        md.heading(9, " Slide\n1 ")
+            .list_item(0, "a")
+            .list_item(1, "b")
+            .paragraph("")
+            .image("chart [Q1]", "my chart.png")
+            .rule();
+        let bullet = md.bullet;
+        assert_eq!(
+            md.finish(),
+            format!("###### Slide 1\n\n{b} a\n  {b} b\n\n![chart \\[Q1\\]](<my chart.png>)\n\n---\n", b = bullet)
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Several inputs converted into one Markdown document that shares a single image directory
+
+use crate::config::SETTINGS;
+use crate::context;
+use crate::generator::image2md;
+use crate::{convert_detailed, write_images, ConvertError, ConverterFile};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Options for `convert_merged`
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    /// `#` level of the per-file section headings
+    pub heading_level: usize,
+    /// Head each section with the whole `file_path` (e.g. `reports/q1.docx`)
+    /// instead of just the file name
+    pub path_headings: bool,
+    /// Note inputs that fail to convert in their section and carry on, instead of aborting
+    pub skip_failures: bool,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            heading_level: 1,
+            path_headings: false,
+            skip_failures: false,
+        }
+    }
+}
+
+/// A merged document and the deduplicated images it links to
+pub(crate) struct Merged {
+    pub markdown: String,
+    pub images: Vec<(PathBuf, Vec<u8>)>,
+}
+
+/// Convert `files` in order into one document: a `# <file name>` section per input,
+/// separated by `---`. Saved images go to `Settings.image_path` once for the whole
+/// set: identical images are stored a single time and clashing names get a `-N` suffix.
+pub fn convert_merged(files: Vec<ConverterFile>, opts: MergeOptions) -> Result<String, ConvertError> {
+    let merged = merge(files, &opts)?;
+    let image_path = SETTINGS.read().unwrap().image_path.clone();
+    write_images(&image_path, &merged.images)?;
+    Ok(merged.markdown)
+}
+
+/// `convert_merged` without writing the images; per-file warnings are
+/// re-recorded (prefixed with the file name) in the surrounding conversion
+pub(crate) fn merge(files: Vec<ConverterFile>, opts: &MergeOptions) -> Result<Merged, ConvertError> {
+    let hashes = "#".repeat(opts.heading_level.clamp(1, 6));
+    // content hash -> stored path, and every stored path so far
+    let mut stored: HashMap<String, PathBuf> = HashMap::new();
+    let mut used_paths: HashSet<PathBuf> = HashSet::new();
+    let mut images = Vec::new();
+    let mut sections = Vec::new();
+    let mut next_image_ref = 1;
+
+    for (index, file) in files.into_iter().enumerate() {
+        let name = file
+            .file_path
+            .as_deref()
+            .and_then(|path| match opts.path_headings {
+                true => Some(path.to_string()),
+                false => Path::new(path).file_name().map(|name| name.to_string_lossy().into_owned()),
+            })
+            .unwrap_or_else(|| format!("Document {}", index + 1));
+
+        // Its own context: converting the file drains it, and must not drain the
+        // warnings already recorded for the files before it
+        let converted = {
+            let _scope = context::Scope::isolated();
+            convert_detailed(file)
+        };
+        let conversion = match converted {
+            Ok(conversion) => conversion,
+            Err(e) if opts.skip_failures => {
+                context::part_failed(format!("{} could not be converted: {}", name, e));
+                sections.push(format!("{} {}\n\n> [Could not be converted: {}]", hashes, name, e));
+                continue;
+            }
+            Err(e) => return Err(ConvertError::Conversion(format!("Failed to convert {}: {}", name, e))),
+        };
+        for warning in &conversion.warnings {
+            context::warn(format!("{}: {}", name, warning));
+        }
+        if conversion.partial {
+            context::mark_partial();
+        }
+        let mut markdown = renumber_image_refs(&conversion.markdown, &mut next_image_ref);
+
+        for (path, data) in conversion.images {
+            let hash = format!("{:x}", Sha256::digest(&data));
+            let target = match stored.get(&hash) {
+                Some(existing) => existing.clone(),
+                None => {
+                    let unique = unique_path(&path, &used_paths);
+                    used_paths.insert(unique.clone());
+                    stored.insert(hash, unique.clone());
+                    images.push((unique.clone(), data));
+                    unique
+                }
+            };
+            if target != path {
+                markdown = relink(&markdown, &path, &target);
+            }
+        }
+
+        sections.push(format!("{} {}\n\n{}", hashes, name, markdown.trim()));
+    }
+
+    let mut markdown = sections.join("\n\n---\n\n");
+    markdown.push('\n');
+    Ok(Merged { markdown, images })
+}
+
+// `![alt][imageN]` uses and `[imageN]: target` definitions (reference_images)
+static IMAGE_REF: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)(\]\[|^\[)(image\d+)\]").unwrap());
+
+// Every file numbers its reference-style images from `image1`; continue the
+// numbering from `*next` so no two sections define the same id
+fn renumber_image_refs(markdown: &str, next: &mut usize) -> String {
+    let mut ids: HashMap<String, String> = HashMap::new();
+    IMAGE_REF
+        .replace_all(markdown, |caps: &Captures| {
+            let id = ids.entry(caps[2].to_string()).or_insert_with(|| {
+                *next += 1;
+                format!("image{}", *next - 1)
+            });
+            format!("{}{}]", &caps[1], id)
+        })
+        .into_owned()
+}
+
+// `name.png`, then `name-2.png`, `name-3.png`... until unused
+fn unique_path(path: &Path, used: &HashSet<PathBuf>) -> PathBuf {
+    if !used.contains(path) {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (2..)
+        .map(|suffix| path.with_file_name(format!("{}-{}{}", stem, suffix, extension)))
+        .find(|candidate| !used.contains(candidate))
+        .unwrap()
+}
+
+// Point the links written for `from` at `to` instead
+fn relink(markdown: &str, from: &Path, to: &Path) -> String {
+    let cfg = SETTINGS.read().unwrap();
+    let old_target = image2md::link_target(&cfg, &from.to_string_lossy());
+    let new_target = image2md::link_target(&cfg, &to.to_string_lossy());
+    markdown
+        .replace(&format!("]({})", old_target), &format!("]({})", new_target))
+        .replace(&format!("]: {}\n", old_target), &format!("]: {}\n", new_target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_path() {
+        let used: HashSet<PathBuf> = ["logo.png", "logo-2.png"].iter().map(PathBuf::from).collect();
+        assert_eq!(unique_path(Path::new("logo.png"), &used), PathBuf::from("logo-3.png"));
+        assert_eq!(unique_path(Path::new("chart.png"), &used), PathBuf::from("chart.png"));
+    }
+
+    #[test]
+    fn test_reference_images_stay_distinct() {
+        let svg = |color: &str| ConverterFile {
+            file_path: Some(format!("{}.svg", color)),
+            file_stream: format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4"><rect width="4" height="4" fill="{}"/></svg>"#,
+                color
+            )
+            .into_bytes(),
+        };
+        let mut settings = crate::config::Settings::builtin();
+        settings.reference_images = true;
+
+        let merged = crate::config::with_settings(settings, || {
+            merge(vec![svg("red"), svg("blue")], &MergeOptions::default())
+        })
+        .unwrap();
+        let definitions: Vec<&str> = merged.markdown.lines().filter(|line| line.starts_with("[image")).collect();
+        assert_eq!(definitions.len(), 2);
+        assert!(definitions[0].starts_with("[image1]: data:image/svg+xml;base64,"));
+        assert!(definitions[1].starts_with("[image2]: data:image/svg+xml;base64,"));
+        assert_ne!(definitions[0][10..], definitions[1][10..]);
+        assert!(merged.markdown.contains("][image2]"));
+    }
+}
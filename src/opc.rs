@@ -0,0 +1,320 @@
+//! Open Packaging Conventions helpers shared by the OOXML readers
+//! (DOCX/PPTX/XLSX are ZIP packages whose parts are linked by `_rels/*.rels`).
+
+use crate::config::SETTINGS;
+use crate::error::ConvertError;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+use zip::read::ZipFile;
+use zip::ZipArchive;
+
+/// An entry from a part's `.rels` file
+#[derive(Debug, Clone)]
+pub struct Relationship {
+    pub target: String,
+    pub rel_type: String,
+    pub external: bool,
+}
+
+/// Document properties from `docProps/core.xml`; dates are kept as written (W3CDTF)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct CoreProperties {
+    pub title: Option<String>,
+    pub creator: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+}
+
+/// `ppt/slides/slide1.xml` -> `ppt/slides/_rels/slide1.xml.rels`
+pub fn rels_path_for(part: &str) -> String {
+    let (dir, file_name) = part.rsplit_once('/').unwrap_or(("", part));
+    if dir.is_empty() {
+        format!("_rels/{}.rels", file_name)
+    } else {
+        format!("{}/_rels/{}.rels", dir, file_name)
+    }
+}
+
+/// Resolve a relationship target relative to the part that owns it,
+/// e.g. (`xl/drawings/drawing1.xml`, `../media/image1.png`) -> `xl/media/image1.png`
+pub fn resolve_target(part: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+
+    let mut segments: Vec<&str> = part.split('/').collect();
+    segments.pop(); // drop the part's own file name
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// Reject packages whose entries declare more uncompressed data than
+/// `Settings.max_zip_entry_bytes` / `max_zip_total_bytes` before anything is inflated.
+pub fn check_archive_limits(data: &[u8]) -> Result<(), ConvertError> {
+    let (entry_limit, total_limit) = {
+        let cfg = SETTINGS.read().unwrap();
+        (cfg.max_zip_entry_bytes, cfg.max_zip_total_bytes)
+    };
+    let mut archive = ZipArchive::new(Cursor::new(data))
+        .map_err(|e| ConvertError::Conversion(format!("Failed to open archive: {}", e)))?;
+
+    let mut total: u64 = 0;
+    for i in 0..archive.len() {
+        let file = archive
+            .by_index_raw(i)
+            .map_err(|e| ConvertError::Conversion(format!("Failed to access file in ZIP archive: {}", e)))?;
+        if file.size() > entry_limit {
+            return Err(ConvertError::ArchiveTooLarge {
+                entry: Some(file.name().to_string()),
+                size: file.size(),
+                limit: entry_limit,
+            });
+        }
+        total = total.saturating_add(file.size());
+        if total > total_limit {
+            return Err(ConvertError::ArchiveTooLarge { entry: None, size: total, limit: total_limit });
+        }
+    }
+    Ok(())
+}
+
+/// DOCX/PPTX/XLSX told apart by their main part, for packages that arrive
+/// without (or with the wrong) extension; None for any other ZIP
+pub fn package_mime_type(data: &[u8]) -> Option<&'static str> {
+    let archive = ZipArchive::new(Cursor::new(data)).ok()?;
+    let names: Vec<&str> = archive.file_names().collect();
+    main_part_mime(&names)
+}
+
+fn main_part_mime(names: &[&str]) -> Option<&'static str> {
+    const MAIN_PARTS: [(&str, &str); 3] = [
+        ("word/document.xml", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+        ("ppt/presentation.xml", "application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+        ("xl/workbook.xml", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+    ];
+    MAIN_PARTS
+        .iter()
+        .find(|(part, _)| names.contains(part))
+        .map(|&(_, mime)| mime)
+}
+
+/// Inflate one entry, stopping at `Settings.max_zip_entry_bytes` even if the
+/// declared size lies
+pub fn read_entry(file: &mut ZipFile) -> Result<Vec<u8>, String> {
+    let limit = SETTINGS.read().unwrap().max_zip_entry_bytes;
+    let mut data = Vec::new();
+    file.take(limit.saturating_add(1))
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Failed to read {}: {}", file.name(), e))?;
+    if data.len() as u64 > limit {
+        return Err(format!("{} expands beyond the {} byte entry limit", file.name(), limit));
+    }
+    Ok(data)
+}
+
+pub fn read_part_to_string<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<String>, String> {
+    match archive.by_name(name) {
+        Ok(mut file) => {
+            let content = String::from_utf8(read_entry(&mut file)?)
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            Ok(Some(content))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+pub fn read_part_bytes<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    match archive.by_name(name) {
+        Ok(mut file) => Ok(Some(read_entry(&mut file)?)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Every part whose name starts with `prefix` (e.g. `ppt/media/`), in archive order.
+/// The inflated parts together may not exceed `Settings.max_zip_total_bytes`.
+pub fn read_parts_under<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    prefix: &str,
+) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let total_limit = SETTINGS.read().unwrap().max_zip_total_bytes;
+    let mut total: u64 = 0;
+    let mut parts = Vec::new();
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to access file in ZIP archive: {}", e))?;
+
+        if file.name().starts_with(prefix) && !file.is_dir() {
+            let data = read_entry(&mut file)?;
+            total = total.saturating_add(data.len() as u64);
+            if total > total_limit {
+                return Err(format!("{} expands beyond the {} byte archive limit", prefix, total_limit));
+            }
+            parts.push((file.name().to_string(), data));
+        }
+    }
+    Ok(parts)
+}
+
+/// Relationships of `part`, keyed by relationship id; a part without rels has none
+pub fn read_rels<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    part: &str,
+) -> Result<HashMap<String, Relationship>, String> {
+    match read_part_to_string(archive, &rels_path_for(part))? {
+        Some(content) => parse_rels(&content),
+        None => Ok(HashMap::new()),
+    }
+}
+
+pub fn parse_rels(xml_content: &str) -> Result<HashMap<String, Relationship>, String> {
+    let mut reader = Reader::from_str(xml_content);
+    let mut rels = HashMap::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) | Ok(Event::Empty(element))
+                if element.name().as_ref() == b"Relationship" =>
+            {
+                let mut id = None;
+                let mut relationship = Relationship {
+                    target: String::new(),
+                    rel_type: String::new(),
+                    external: false,
+                };
+                for attr_result in element.attributes() {
+                    let attr = attr_result.map_err(|e| format!("Error reading attribute: {}", e))?;
+                    let value = attr.unescape_value().unwrap_or_default().to_string();
+                    match attr.key.as_ref() {
+                        b"Id" => id = Some(value),
+                        b"Target" => relationship.target = value,
+                        b"Type" => relationship.rel_type = value,
+                        b"TargetMode" => relationship.external = value == "External",
+                        _ => {}
+                    }
+                }
+                if let Some(id) = id {
+                    rels.insert(id, relationship);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Error parsing relationships: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(rels)
+}
+
+/// Value of the `r:id`/`r:embed` style attribute `key` on an element
+pub fn attribute_value(
+    element: &quick_xml::events::BytesStart,
+    key: &[u8],
+) -> Result<Option<String>, String> {
+    for attr_result in element.attributes() {
+        let attr = attr_result.map_err(|e| format!("Error reading attribute: {}", e))?;
+        if attr.key.as_ref() == key {
+            return Ok(Some(attr.unescape_value().unwrap_or_default().to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Title, author, dates etc. from `docProps/core.xml` of a DOCX/PPTX/XLSX package;
+/// None when missing or unreadable
+pub fn core_properties(file_stream: &[u8]) -> Option<CoreProperties> {
+    let mut archive = ZipArchive::new(Cursor::new(file_stream)).ok()?;
+    let core_xml = read_part_to_string(&mut archive, "docProps/core.xml").ok()??;
+    parse_core_properties(&core_xml).ok()
+}
+
+pub fn parse_core_properties(xml_content: &str) -> Result<CoreProperties, String> {
+    let mut reader = Reader::from_str(xml_content);
+    let mut properties = CoreProperties::default();
+    let mut current: Option<Vec<u8>> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => current = Some(element.local_name().as_ref().to_vec()),
+            Ok(Event::Text(text)) => {
+                let value = text
+                    .unescape()
+                    .map_err(|e| format!("Error parsing core properties: {}", e))?
+                    .trim()
+                    .to_string();
+                let field = match current.as_deref() {
+                    Some(b"title") => &mut properties.title,
+                    Some(b"creator") => &mut properties.creator,
+                    Some(b"subject") => &mut properties.subject,
+                    Some(b"keywords") => &mut properties.keywords,
+                    Some(b"created") => &mut properties.created,
+                    Some(b"modified") => &mut properties.modified,
+                    _ => continue,
+                };
+                if !value.is_empty() {
+                    *field = Some(value);
+                }
+            }
+            Ok(Event::End(_)) => current = None,
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Error parsing core properties: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_main_part_mime() {
+        let pptx = ["[Content_Types].xml", "ppt/slides/slide1.xml", "ppt/presentation.xml"];
+        assert_eq!(
+            main_part_mime(&pptx),
+            Some("application/vnd.openxmlformats-officedocument.presentationml.presentation")
+        );
+        assert_eq!(main_part_mime(&["notes/word/document.xml", "readme.txt"]), None);
+    }
+
+    #[test]
+    fn test_parse_core_properties() {
+        let xml = r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/">
+            <dc:title>Quarterly &amp; Annual Report</dc:title>
+            <dc:creator>Ada</dc:creator>
+            <cp:keywords>finance, q3</cp:keywords>
+            <dcterms:created xsi:type="dcterms:W3CDTF">2024-01-02T03:04:05Z</dcterms:created>
+        </cp:coreProperties>"#;
+        let properties = parse_core_properties(xml).unwrap();
+        assert_eq!(properties.title.as_deref(), Some("Quarterly & Annual Report"));
+        assert_eq!(properties.creator.as_deref(), Some("Ada"));
+        assert_eq!(properties.keywords.as_deref(), Some("finance, q3"));
+        assert_eq!(properties.created.as_deref(), Some("2024-01-02T03:04:05Z"));
+        assert_eq!(properties.subject, None);
+    }
+}
@@ -0,0 +1,218 @@
+//! Optional clean-up passes applied to the generated Markdown in `convert`
+
+/// Replace Office "smart" typography with plain ASCII equivalents:
+/// curly quotes become straight quotes, em/en dashes become `--`/`-`,
+/// and non-breaking spaces become regular spaces.
+pub fn normalize_typography(markdown: &str) -> String {
+    let mut normalized = String::with_capacity(markdown.len());
+    for c in markdown.chars() {
+        match c {
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => normalized.push('\''),
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => normalized.push('"'),
+            '\u{2014}' => normalized.push_str("--"),
+            '\u{2013}' => normalized.push('-'),
+            '\u{00A0}' | '\u{202F}' => normalized.push(' '),
+            _ => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+/// Spacing clean-up run on every conversion (`Settings.tidy_blank_lines`): runs of
+/// blank lines become one, leading blank lines and trailing whitespace go (two-space
+/// hard breaks become a trailing `\`), and the text ends in a single newline.
+/// Fenced code is left untouched.
+pub fn tidy_blank_lines(markdown: &str) -> String {
+    walk_lines(markdown, |out, raw, next| {
+        let line = raw.trim_end();
+        if fence_marker(line).is_none() && is_hard_break(raw, next) {
+            out.push(format!("{}\\", line));
+        } else {
+            out.push(line.to_string());
+        }
+    })
+}
+
+/// Markdown lint fixes (`Settings.lint_output`): everything [`tidy_blank_lines`]
+/// does, plus a blank line around headings and tables.
+pub fn lint_markdown(markdown: &str) -> String {
+    // the previous block (heading, table) wants a blank line before the next one
+    let mut blank_after = false;
+
+    walk_lines(&tidy_blank_lines(markdown), |out, line, _| {
+        let is_table = line.trim_start().starts_with('|');
+        let after_table = out.last().is_some_and(|last| last.trim_start().starts_with('|'));
+        if blank_after || is_table != after_table {
+            push_blank(out);
+        }
+        blank_after = false;
+
+        if fence_marker(line).is_some() {
+            out.push(line.to_string());
+        } else if is_atx_heading(line) {
+            push_blank(out);
+            out.push(line.to_string());
+            blank_after = true;
+        } else if is_setext_underline(line, out) {
+            let text = out.pop().unwrap_or_default();
+            push_blank(out);
+            out.push(text);
+            out.push(line.to_string());
+            blank_after = true;
+        } else {
+            out.push(line.to_string());
+        }
+    })
+}
+
+// The line walk shared by the clean-up passes: fenced code is copied as is, blank
+// lines collapse into one, and every other line goes to `push_line` together with
+// the line after it. Trailing blank lines are dropped and the text ends in a single
+// newline.
+fn walk_lines(markdown: &str, mut push_line: impl FnMut(&mut Vec<String>, &str, Option<&&str>)) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::new();
+    // closing marker of the open code fence
+    let mut fence: Option<String> = None;
+
+    for (index, raw) in lines.iter().enumerate() {
+        if let Some(marker) = &fence {
+            if closes_fence(marker, raw) {
+                fence = None;
+            }
+            out.push(raw.to_string());
+            continue;
+        }
+
+        if raw.trim_end().is_empty() {
+            push_blank(&mut out);
+            continue;
+        }
+        push_line(&mut out, raw, lines.get(index + 1));
+        fence = fence_marker(raw.trim_end());
+    }
+
+    while out.last().is_some_and(|last| last.is_empty()) {
+        out.pop();
+    }
+    if out.is_empty() {
+        return String::new();
+    }
+    let mut walked = out.join("\n");
+    walked.push('\n');
+    walked
+}
+
+fn push_blank(out: &mut Vec<String>) {
+    if out.last().is_some_and(|last| !last.is_empty()) {
+        out.push(String::new());
+    }
+}
+
+// A closing fence is at least as long as the opening one and has no info string
+fn closes_fence(marker: &str, line: &str) -> bool {
+    fence_marker(line).is_some_and(|closing| closing.starts_with(marker) && closing.len() == line.trim().len())
+}
+
+// Two trailing spaces with more paragraph text on the next line
+fn is_hard_break(raw: &str, next: Option<&&str>) -> bool {
+    raw.ends_with("  ") && !raw.trim_start().starts_with('|') && next.is_some_and(|next| is_paragraph_text(next))
+}
+
+// "```rust" -> "```", "~~~~" -> "~~~~"
+pub(crate) fn fence_marker(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let marker_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let marker: String = trimmed.chars().take_while(|c| *c == marker_char).collect();
+    (marker.len() >= 3).then_some(marker)
+}
+
+fn is_atx_heading(line: &str) -> bool {
+    let hashes = line.bytes().take_while(|b| *b == b'#').count();
+    (1..=6).contains(&hashes) && line[hashes..].chars().next().is_none_or(|c| c == ' ')
+}
+
+// `===`/`---` under a single line of paragraph text
+fn is_setext_underline(line: &str, out: &[String]) -> bool {
+    let underline = (!line.is_empty() && line.bytes().all(|b| b == b'='))
+        || (line.len() >= 3 && line.bytes().all(|b| b == b'-'));
+    let text = match out {
+        [text] => text,
+        [.., before, text] if before.is_empty() => text,
+        _ => return false,
+    };
+    underline && is_paragraph_text(text)
+}
+
+fn is_paragraph_text(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty()
+        && line.len() - trimmed.len() < 4
+        && !trimmed.starts_with(['#', '|', '>', '-', '*', '+'])
+        && !trimmed.split_once(". ").is_some_and(|(number, _)| number.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// GitHub-style anchor for a heading: lower-cased, punctuation dropped, spaces
+/// as `-` (`"Q3 Results: Europe"` -> `q3-results-europe`). Inline Markdown around
+/// the text (emphasis, code, link targets) is not part of the anchor.
+pub fn heading_slug(text: &str) -> String {
+    let text = strip_link_targets(text);
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// `[text](target)` -> `text`
+fn strip_link_targets(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("](") {
+        let Some(end) = rest[start..].find(')') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_typography() {
+        let input = "\u{201C}Quoted\u{201D} it\u{2019}s 1\u{2013}2 \u{2014} done\u{00A0}now";
+        assert_eq!(normalize_typography(input), "\"Quoted\" it's 1-2 -- done now");
+    }
+
+    #[test]
+    fn test_heading_slug() {
+        assert_eq!(heading_slug("Q3 Results: Europe"), "q3-results-europe");
+        assert_eq!(heading_slug("**Über** [uns](https://example.com/about) & `more`"), "über-uns--more");
+    }
+
+    #[test]
+    fn test_tidy_blank_lines() {
+        let input = "\n\n# Slide 1\t\n\n\n\n---\n\nStreet  \nCity\n```\nkeep  \n\n\n```\n\n\n";
+        assert_eq!(tidy_blank_lines(input), "# Slide 1\n\n---\n\nStreet\\\nCity\n```\nkeep  \n\n\n```\n");
+        assert_eq!(tidy_blank_lines("\n \n"), "");
+    }
+
+    #[test]
+    fn test_lint_markdown() {
+        let input = "Title\n=====\nIntro  \nnext line  \n# Data\n| a |\n|---|\n| 1 |\nAfter\n\n\n\n```\ncode  \n# not a heading\n```\n\n";
+        assert_eq!(
+            lint_markdown(input),
+            "Title\n=====\n\nIntro\\\nnext line\n\n# Data\n\n| a |\n|---|\n| 1 |\n\nAfter\n\n```\ncode  \n# not a heading\n```\n"
+        );
+    }
+}
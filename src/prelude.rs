@@ -0,0 +1,25 @@
+//! The types a typical integrator needs, in one import:
+//!
+//! ```rust
+//! use markitup::prelude::*;
+//! ```
+
+pub use crate::config::{get_settings, init_settings, try_settings, Settings};
+pub use crate::converter::audio2wav::AudioConversionError;
+pub use crate::converter::xlsx2csv::Xlsx2CsvConfig;
+pub use crate::error::ConvertError;
+pub use crate::formats::{supported_formats, FormatInfo};
+pub use crate::generator::docx2md::{RtlMarkup, TrackChangesMode};
+pub use crate::generator::image2md::{ImageLinkStyle, ImageProcessingMode};
+pub use crate::merge::{convert_merged, MergeOptions};
+pub use crate::opc::CoreProperties;
+pub use crate::split::{split_by_heading, write_sections, Section};
+pub use crate::table::TableStyle;
+pub use crate::{
+    clear_cache, convert, convert_bytes_as, convert_cached, convert_cached_detailed, convert_detailed,
+    convert_from_path, convert_from_path_detailed, convert_preview, convert_to_html, convert_with_detected,
+    extract_images, markdown_to_html, write_images,
+    ConvertOptions, Conversion, ConverterFile,
+};
+#[cfg(feature = "async")]
+pub use crate::async_api::convert_async;
@@ -0,0 +1,107 @@
+//! One Markdown file per section: the converted document cut at every heading of a given level
+
+use crate::error::ConvertError;
+use crate::postprocess::{fence_marker, heading_slug};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// File name for the content before the first heading
+pub const INDEX_FILE_NAME: &str = "index.md";
+
+/// One piece of a split document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// `<heading slug>.md`, or `index.md` for the content before the first heading
+    pub file_name: String,
+    pub markdown: String,
+}
+
+/// Cut `markdown` before every ATX heading of exactly `level` (1-6); headings inside
+/// code fences don't count. Clashing slugs get a `-1`, `-2`... suffix as GitHub anchors do,
+/// and an untitled heading becomes `section-N.md`.
+pub fn split_by_heading(markdown: &str, level: usize) -> Vec<Section> {
+    let prefix = format!("{} ", "#".repeat(level.clamp(1, 6)));
+    // "index" is taken by the preamble
+    let mut used: HashSet<String> = HashSet::from(["index".to_string()]);
+    let mut sections = Vec::new();
+    let mut current = Section {
+        file_name: INDEX_FILE_NAME.to_string(),
+        markdown: String::new(),
+    };
+    // closing marker of the open code fence
+    let mut fence: Option<String> = None;
+
+    for line in markdown.lines() {
+        if let Some(marker) = &fence {
+            if fence_marker(line).is_some_and(|closing| closing.starts_with(marker.as_str())) {
+                fence = None;
+            }
+        } else if let Some(marker) = fence_marker(line) {
+            fence = Some(marker);
+        } else if let Some(title) = line.strip_prefix(&prefix) {
+            push_section(&mut sections, current);
+            let title = title.trim().trim_end_matches('#').trim_end();
+            current = Section {
+                file_name: format!("{}.md", unique_slug(title, sections.len() + 1, &mut used)),
+                markdown: String::new(),
+            };
+        }
+        current.markdown.push_str(line);
+        current.markdown.push('\n');
+    }
+    push_section(&mut sections, current);
+    sections
+}
+
+/// Write `sections` into `dir` (created if missing); returns the written paths
+pub fn write_sections(dir: &Path, sections: &[Section]) -> Result<Vec<PathBuf>, ConvertError> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| ConvertError::Io(format!("Failed to create output directory: {}", e)))?;
+    let mut written = Vec::new();
+    for section in sections {
+        let path = dir.join(&section.file_name);
+        std::fs::write(&path, &section.markdown)
+            .map_err(|e| ConvertError::Io(format!("Failed to write {}: {}", path.display(), e)))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+// A blank preamble is dropped, everything else trimmed to a single final newline
+fn push_section(sections: &mut Vec<Section>, mut section: Section) {
+    if section.markdown.trim().is_empty() {
+        return;
+    }
+    section.markdown = format!("{}\n", section.markdown.trim());
+    sections.push(section);
+}
+
+fn unique_slug(title: &str, number: usize, used: &mut HashSet<String>) -> String {
+    let slug = heading_slug(title);
+    let slug = if slug.trim_matches('-').is_empty() { format!("section-{}", number) } else { slug };
+    let unique = (0..)
+        .map(|suffix| match suffix {
+            0 => slug.clone(),
+            n => format!("{}-{}", slug, n),
+        })
+        .find(|candidate| !used.contains(candidate))
+        .unwrap();
+    used.insert(unique.clone());
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_by_heading() {
+        let markdown = "# Report\n\nIntro\n\n## Setup\n\nsteps\n\n```sh\n## not a section\n```\n\n## Setup\n\nagain\n\n### Detail\n\n## Index\n";
+        let sections = split_by_heading(markdown, 2);
+        let names: Vec<&str> = sections.iter().map(|section| section.file_name.as_str()).collect();
+        assert_eq!(names, ["index.md", "setup.md", "setup-1.md", "index-1.md"]);
+        assert_eq!(sections[0].markdown, "# Report\n\nIntro\n");
+        assert!(sections[1].markdown.contains("## not a section"));
+        assert!(sections[2].markdown.ends_with("### Detail\n"));
+    }
+}
@@ -0,0 +1,111 @@
+//! Document statistics computed from the generated Markdown
+
+/// Average silent reading speed used for `reading_minutes`
+pub const WORDS_PER_MINUTE: f32 = 200.0;
+
+/// Count the words a reader would see, ignoring Markdown syntax:
+/// link/image targets, HTML tags and punctuation-only tokens (`|---|`, `#`, `-`)
+/// are skipped. CJK characters count as one word each.
+pub fn word_count(markdown: &str) -> usize {
+    // Reference definitions (`[image1]: data:...`) are link targets too
+    let body: String = markdown
+        .lines()
+        .filter(|line| !is_reference_definition(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = strip_markup(&body);
+    let mut count = 0;
+
+    for token in text.split_whitespace() {
+        let mut has_word = false;
+        for c in token.chars() {
+            if is_cjk(c) {
+                count += 1;
+            } else if c.is_alphanumeric() {
+                has_word = true;
+            }
+        }
+        if has_word {
+            count += 1;
+        }
+    }
+
+    count
+}
+
+pub fn reading_minutes(word_count: usize) -> f32 {
+    word_count as f32 / WORDS_PER_MINUTE
+}
+
+// Drop `](target)` link/image targets and `<...>` tags, keeping link text
+fn strip_markup(markdown: &str) -> String {
+    let mut text = String::with_capacity(markdown.len());
+    let mut chars = markdown.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ']' if chars.peek() == Some(&'(') => {
+                let mut depth = 0;
+                for c in chars.by_ref() {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                text.push(' ');
+            }
+            '<' => {
+                for c in chars.by_ref() {
+                    if c == '>' {
+                        break;
+                    }
+                }
+                text.push(' ');
+            }
+            _ => text.push(c),
+        }
+    }
+
+    text
+}
+
+fn is_reference_definition(line: &str) -> bool {
+    line.starts_with('[')
+        && line
+            .find("]: ")
+            .is_some_and(|end| !line[1..end].contains(['[', ']']))
+}
+
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'   // 平假名/片假名
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}' // 韩文
+        | '\u{F900}'..='\u{FAFF}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_count_ignores_syntax() {
+        let markdown = "# Title\n\n| a | b |\n|---|---|\n| one | two |\n\n\
+                        See [the docs](https://example.com/a_b) ![logo](data:image/png;base64,AAAA)\n\n\
+                        - it's done<br>\n\n[image1]: data:image/png;base64,AAAA\n";
+        // Title, a, b, one, two, See, the, docs, logo, it's, done
+        assert_eq!(word_count(markdown), 11);
+    }
+
+    #[test]
+    fn test_word_count_cjk() {
+        assert_eq!(word_count("中文文档 test"), 5);
+    }
+}
@@ -0,0 +1,230 @@
+//! Table rendering shared by the DOCX/PPTX/CSV/XLSX generators
+
+use serde::{Deserialize, Serialize};
+
+/// How `render_table` writes tables (`Settings.table_style`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    /// Always a pipe table; multi-line cells are flattened onto one line
+    Markdown,
+    /// Pipe tables for simple grids, raw `<table>` HTML for tables they can't express
+    Html,
+    /// Always raw `<table>` HTML; what the CommonMark flavor uses, since it has no pipe tables
+    #[serde(skip)]
+    HtmlOnly,
+    /// A fenced ```` ```csv ```` block with the raw cell text, for data that another tool
+    /// will re-parse; alignment and `empty_cell` don't apply
+    #[serde(rename = "csv")]
+    CsvBlock,
+}
+
+/// Horizontal alignment of a table column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    /// Whatever the renderer does by default (`---`)
+    #[default]
+    Default,
+    Left,
+    Center,
+    Right,
+}
+
+/// Render `rows` (the first one is the header) as a Markdown table.
+/// Cells may contain `\n` for separate lines/paragraphs; blank cells, including
+/// the padding of short rows, are written as `empty_cell`.
+pub fn render_table(rows: &[Vec<String>], style: TableStyle, empty_cell: &str) -> String {
+    render_aligned_table(rows, &[], style, empty_cell)
+}
+
+/// `render_table` with per-column alignment; columns past the end of `alignments` use the default
+pub fn render_aligned_table(
+    rows: &[Vec<String>],
+    alignments: &[Alignment],
+    style: TableStyle,
+    empty_cell: &str,
+) -> String {
+    if rows.is_empty() {
+        return String::new();
+    }
+
+    if style == TableStyle::CsvBlock {
+        render_csv_block(rows)
+    } else if style == TableStyle::HtmlOnly || (style == TableStyle::Html && needs_html(rows)) {
+        render_html(rows, alignments, empty_cell)
+    } else {
+        render_pipe(rows, alignments, empty_cell)
+    }
+}
+
+// Multi-line cells and ragged rows (merged cells) have no pipe-table equivalent
+fn needs_html(rows: &[Vec<String>]) -> bool {
+    let width = rows[0].len();
+    rows.iter()
+        .any(|row| row.len() != width || row.iter().any(|cell| cell.trim().contains('\n')))
+}
+
+fn render_pipe(rows: &[Vec<String>], alignments: &[Alignment], empty_cell: &str) -> String {
+    // Every row gets the same number of pipes
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut markdown = String::new();
+
+    for (index, row) in rows.iter().enumerate() {
+        markdown.push('|');
+        for column in 0..width {
+            let cell = pipe_cell(row.get(column).map(String::as_str).unwrap_or_default());
+            let cell = if cell.is_empty() { pipe_cell(empty_cell) } else { cell };
+            markdown.push_str(&format!(" {} |", cell));
+        }
+        markdown.push('\n');
+
+        if index == 0 {
+            markdown.push('|');
+            for column in 0..width {
+                let separator = match alignments.get(column).copied().unwrap_or_default() {
+                    Alignment::Default => "---",
+                    Alignment::Left => ":--",
+                    Alignment::Center => ":-:",
+                    Alignment::Right => "--:",
+                };
+                markdown.push_str(separator);
+                markdown.push('|');
+            }
+            markdown.push('\n');
+        }
+    }
+
+    markdown
+}
+
+fn pipe_cell(cell: &str) -> String {
+    cell.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+fn render_html(rows: &[Vec<String>], alignments: &[Alignment], empty_cell: &str) -> String {
+    let mut html = String::from("<table>\n");
+
+    for (index, row) in rows.iter().enumerate() {
+        let tag = if index == 0 { "th" } else { "td" };
+        html.push_str("<tr>");
+        for (column, cell) in row.iter().enumerate() {
+            let cell = if cell.trim().is_empty() { empty_cell } else { cell.as_str() };
+            let align = match alignments.get(column).copied().unwrap_or_default() {
+                Alignment::Default => "",
+                Alignment::Left => " align=\"left\"",
+                Alignment::Center => " align=\"center\"",
+                Alignment::Right => " align=\"right\"",
+            };
+            html.push_str(&format!("<{}{}>{}</{}>", tag, align, html_cell(cell), tag));
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+fn render_csv_block(rows: &[Vec<String>]) -> String {
+    // Every record gets the same number of fields
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let csv: String = rows
+        .iter()
+        .map(|row| {
+            let fields: Vec<String> = (0..width)
+                .map(|column| csv_field(row.get(column).map(|cell| cell.trim()).unwrap_or_default()))
+                .collect();
+            format!("{}\n", fields.join(","))
+        })
+        .collect();
+
+    // The fence must be longer than any backtick run in the data
+    let longest_run = csv.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{}csv\n{}{}\n", fence, csv, fence)
+}
+
+// RFC 4180: quoted when it holds a comma, quote or line break; quotes doubled
+fn csv_field(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+fn html_cell(cell: &str) -> String {
+    cell.trim()
+        .lines()
+        .map(|line| {
+            line.trim()
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(cells: &[&[&str]]) -> Vec<Vec<String>> {
+        cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_simple_table_stays_pipe_table() {
+        let table = rows(&[&["a", "b"], &["1", "x|y"]]);
+        let expected = "| a | b |\n|---|---|\n| 1 | x\\|y |\n";
+        assert_eq!(render_table(&table, TableStyle::Markdown, ""), expected);
+        assert_eq!(render_table(&table, TableStyle::Html, ""), expected);
+    }
+
+    #[test]
+    fn test_multiline_cell_falls_back_to_html() {
+        let table = rows(&[&["Name", "Notes"], &["a<b", "first\nsecond"]]);
+        assert_eq!(
+            render_table(&table, TableStyle::Markdown, ""),
+            "| Name | Notes |\n|---|---|\n| a<b | first second |\n"
+        );
+        assert_eq!(
+            render_table(&table, TableStyle::Html, ""),
+            "<table>\n<tr><th>Name</th><th>Notes</th></tr>\n\
+             <tr><td>a&lt;b</td><td>first<br>second</td></tr>\n</table>\n"
+        );
+    }
+
+    #[test]
+    fn test_column_alignment_separators() {
+        let table = rows(&[&["Item", "Qty", "Note"], &["a", "1", "x"]]);
+        assert_eq!(
+            render_aligned_table(&table, &[Alignment::Left, Alignment::Right], TableStyle::Markdown, ""),
+            "| Item | Qty | Note |\n|:--|--:|---|\n| a | 1 | x |\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_block() {
+        let table = rows(&[&["Item", "Note"], &["a, b", "say \"hi\"\nthen ```"], &["c"]]);
+        assert_eq!(
+            render_table(&table, TableStyle::CsvBlock, "-"),
+            "````csv\nItem,Note\n\"a, b\",\"say \"\"hi\"\"\nthen ```\"\nc,\n````\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_cell_placeholder() {
+        let table = rows(&[&["a", ""], &["", "2"], &["3"]]);
+        assert_eq!(
+            render_table(&table, TableStyle::Markdown, "-"),
+            "| a | - |\n|---|---|\n| - | 2 |\n| 3 | - |\n"
+        );
+    }
+}
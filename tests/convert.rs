@@ -0,0 +1,112 @@
+//! End-to-end conversions of the sample files in `tests/fixtures`.
+//! `<name>.md` next to a fixture is its golden output, compared exactly;
+//! the other formats are checked for the content that must survive.
+
+use markitup::{convert_from_path, ConvertError};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn convert_fixture(name: &str) -> String {
+    convert_from_path(&fixture(name)).unwrap_or_else(|e| panic!("converting {} failed: {}", name, e))
+}
+
+fn assert_golden(name: &str) {
+    let expected = std::fs::read_to_string(fixture(&format!("{}.md", name))).unwrap();
+    assert_eq!(convert_fixture(name).trim_end(), expected.trim_end());
+}
+
+fn assert_contains_all(markdown: &str, expected: &[&str]) {
+    for needle in expected {
+        assert!(markdown.contains(needle), "missing {:?} in:\n{}", needle, markdown);
+    }
+}
+
+#[test]
+fn csv_matches_golden() {
+    assert_golden("sample.csv");
+}
+
+#[test]
+fn html_matches_golden() {
+    assert_golden("sample.html");
+}
+
+#[test]
+fn docx_keeps_headings_text_and_tables() {
+    let markdown = convert_fixture("sample.docx");
+    // pandoc (when installed) and the built-in reader format tables differently
+    assert_contains_all(&markdown, &["Quarterly Report", "Revenue grew steadily this quarter.", "Region", "North", "120"]);
+    assert!(markdown.contains("# Quarterly Report"), "heading lost in:\n{}", markdown);
+}
+
+#[test]
+fn docx_checkbox_controls_become_task_items() {
+    let markdown = convert_fixture("checklist.docx");
+    assert_contains_all(&markdown, &["- [x] Tests pass", "- [ ] Changelog updated"]);
+}
+
+#[test]
+fn pptx_keeps_slides_and_bullets() {
+    let markdown = convert_fixture("sample.pptx");
+    assert_contains_all(&markdown, &["# Sample Deck", "## Slide 1", "Welcome", "- First point", "- Second point"]);
+}
+
+#[test]
+fn xlsx_renders_each_sheet_as_table() {
+    let markdown = convert_fixture("sample.xlsx");
+    assert_contains_all(&markdown, &["## Sheet: Inventory", "| Item | Count |", "| Apples | 12", "| Pears | 7"]);
+}
+
+#[test]
+#[ignore = "needs a Vosk model at Settings.model_path"]
+fn wav_is_transcribed() {
+    let markdown = convert_fixture("sample.wav");
+    assert_contains_all(&markdown, &["# Audio Transcription", "**Sample Rate**: 16000 Hz", "**Duration**: 00:00:00.500"]);
+}
+
+#[test]
+fn unknown_binary_is_rejected() {
+    let file = markitup::ConverterFile {
+        file_path: None,
+        file_stream: vec![0, 159, 146, 150, 0, 1, 2, 3],
+    };
+    assert!(matches!(markitup::convert(file), Err(ConvertError::UnknownType)));
+}
+
+#[test]
+fn preview_is_cut_to_the_character_budget() {
+    let file = markitup::ConverterFile {
+        file_path: Some(fixture("sample.csv")),
+        file_stream: std::fs::read(fixture("sample.csv")).unwrap(),
+    };
+    let preview = markitup::convert_preview(file, 20).unwrap();
+    assert!(preview.starts_with("| name | role | city"), "{}", preview);
+    assert!(preview.ends_with(markitup::PREVIEW_TRUNCATION_MARKER), "{}", preview);
+}
+
+#[test]
+fn bytes_convert_with_explicit_mime_and_settings() {
+    let csv = std::fs::read(fixture("sample.csv")).unwrap();
+    let gfm = markitup::convert_bytes_as(&csv, "text/csv; charset=utf-8", &Default::default()).unwrap();
+    assert!(gfm.markdown.starts_with("| name | role | city"), "{}", gfm.markdown);
+
+    let settings = markitup::config::Settings::from_toml_str("flavor = \"commonmark\"").unwrap();
+    let options = markitup::ConvertOptions::new(settings);
+    let commonmark = markitup::convert_bytes_as(&csv, "text/csv", &options).unwrap();
+    assert!(commonmark.markdown.starts_with("<table>"), "{}", commonmark.markdown);
+}
+
+#[test]
+fn per_format_entry_skips_detection() {
+    // No extension and no signature: `convert` would have to guess
+    let markdown = markitup::csv_to_markdown(b"name,role\nAda,engineer\n").unwrap();
+    assert!(markdown.starts_with("| name | role |"), "{}", markdown);
+}